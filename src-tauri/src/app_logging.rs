@@ -0,0 +1,188 @@
+// Application logging
+// `log` facade -> stderr + {data_dir}/logs/janus.log, rotated by size (5 files × 5 MB, 0600).
+// Sensitive values never reach this layer in clear: callers go through secure_log / log_address.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LOG_FILE: &str = "janus.log";
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+// janus.log + janus.log.1 … janus.log.4
+const KEEP_FILES: usize = 5;
+
+pub fn log_dir() -> PathBuf {
+    crate::get_data_base_dir().join("logs")
+}
+
+/// `log_level` setting values (error, warn, info, debug, trace)
+pub fn parse_level(value: &str) -> Option<LevelFilter> {
+    match value.trim().to_lowercase().as_str() {
+        "off" => Some(LevelFilter::Off),
+        "error" => Some(LevelFilter::Error),
+        "warn" => Some(LevelFilter::Warn),
+        "info" => Some(LevelFilter::Info),
+        "debug" => Some(LevelFilter::Debug),
+        "trace" => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+struct RotatingFile {
+    dir: PathBuf,
+    max_size: u64,
+    file: Option<(File, u64)>,
+}
+
+impl RotatingFile {
+    fn new(dir: PathBuf, max_size: u64) -> Self {
+        RotatingFile { dir, max_size, file: None }
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64;
+        if self.file.as_ref().is_some_and(|(_, size)| *size > 0 && size + len > self.max_size) {
+            self.file = None;
+            rotate(&self.dir)?;
+        }
+        if self.file.is_none() {
+            let file = open_private(&self.dir.join(LOG_FILE))?;
+            let size = file.metadata()?.len();
+            self.file = Some((file, size));
+        }
+        if let Some((file, size)) = self.file.as_mut() {
+            file.write_all(line.as_bytes())?;
+            *size += len;
+        }
+        Ok(())
+    }
+}
+
+fn open_private(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/// janus.log.3 -> .4, …, janus.log -> .1 (the oldest one is dropped)
+fn rotate(dir: &Path) -> std::io::Result<()> {
+    let rotated = |n: usize| dir.join(format!("{}.{}", LOG_FILE, n));
+    std::fs::remove_file(rotated(KEEP_FILES - 1)).ok();
+    for n in (1..KEEP_FILES - 1).rev() {
+        if rotated(n).exists() {
+            std::fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    std::fs::rename(dir.join(LOG_FILE), rotated(1))
+}
+
+struct FileLogger {
+    file: Mutex<RotatingFile>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}\n",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
+            record.level(),
+            record.args()
+        );
+        eprint!("{}", line);
+        if let Ok(mut file) = self.file.lock() {
+            // Losing a log line must never break the command that logged it
+            file.write_line(&line).ok();
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some((f, _)) = file.file.as_mut() {
+                f.flush().ok();
+            }
+        }
+    }
+}
+
+/// Installs the file logger (once, after the data directory is known).
+pub fn init(level: LevelFilter) -> Result<(), String> {
+    let logger = FileLogger { file: Mutex::new(RotatingFile::new(log_dir(), MAX_LOG_SIZE)) };
+    log::set_boxed_logger(Box::new(logger)).map_err(|e| e.to_string())?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Last `lines` lines, reaching into janus.log.1 when the current file is shorter.
+pub fn recent_lines(lines: usize) -> Result<Vec<String>, String> {
+    let dir = log_dir();
+    let mut out: Vec<String> = Vec::new();
+    for path in [dir.join(LOG_FILE), dir.join(format!("{}.1", LOG_FILE))] {
+        if out.len() >= lines {
+            break;
+        }
+        let content = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Lecture du log impossible : {}", e)),
+        };
+        let mut older: Vec<String> = content.lines().rev().take(lines - out.len()).map(str::to_string).collect();
+        older.reverse();
+        older.append(&mut out);
+        out = older;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_five_private_files() {
+        let dir = std::env::temp_dir().join(format!("janus_logs_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let mut file = RotatingFile::new(dir.clone(), 100);
+        for i in 0..40 {
+            file.write_line(&format!("line {:02} ..............\n", i)).unwrap();
+        }
+        let mut names: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["janus.log", "janus.log.1", "janus.log.2", "janus.log.3", "janus.log.4"]);
+        for name in &names {
+            let meta = std::fs::metadata(dir.join(name)).unwrap();
+            assert!(meta.len() <= 100);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+            }
+        }
+        let current = std::fs::read_to_string(dir.join(LOG_FILE)).unwrap();
+        assert!(current.ends_with("line 39 ..............\n"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("Debug"), Some(LevelFilter::Debug));
+        assert_eq!(parse_level("verbose"), None);
+    }
+}
@@ -0,0 +1,1129 @@
+use crate::error::JanusError;
+use crate::http::FetchError;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::future::Future;
+use std::pin::Pin;
+
+//
+// SOURCES DE BALANCE ON-CHAIN
+//
+
+/// Decimals of the smallest unit of each chain (satoshi, wei, yoctoNEAR…)
+const SATS_DECIMALS: u32 = 8;
+const WEI_DECIMALS: u32 = 18;
+const YOCTO_DECIMALS: u32 = 24;
+const LOVELACE_DECIMALS: u32 = 6;
+const DROPS_DECIMALS: u32 = 6;
+const LAMPORTS_DECIMALS: u32 = 9;
+const PLANCK_DECIMALS: u32 = 10;
+/// Free-tier quotas are per minute
+const RATE_LIMIT_RETRY_SECS: u64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    #[error("network: {0}")]
+    Network(String),
+    #[error("HTTP {0}")]
+    Status(u16),
+    #[error("invalid response: {0}")]
+    Parse(String),
+    #[error("no balance in response")]
+    Missing,
+}
+
+/// Balance as an integer count of the smallest unit, so that 8-decimal BTC or 18-decimal wei
+/// values never go through f64 arithmetic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amount {
+    pub atomic: u128,
+    pub decimals: u32,
+}
+
+impl Amount {
+    pub fn new(atomic: u128, decimals: u32) -> Self {
+        Self { atomic, decimals }
+    }
+
+    /// Integer string of smallest units ("29999999")
+    pub fn parse_atomic(value: &str, decimals: u32) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        value.parse().ok().map(|atomic| Self::new(atomic, decimals))
+    }
+
+    /// Decimal string in whole coins ("0.29999999"); None past `decimals` fractional digits
+    pub fn parse_coins(value: &str, decimals: u32) -> Option<Self> {
+        match Self::round_coins(value, decimals)? {
+            (amount, true) => Some(amount),
+            (_, false) => None,
+        }
+    }
+
+    /// Decimal string in whole coins rounded to `decimals`; the flag is false when digits were lost
+    fn round_coins(value: &str, decimals: u32) -> Option<(Self, bool)> {
+        let (whole, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        if !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let kept = &fraction[..fraction.len().min(decimals as usize)];
+        let dropped = &fraction[kept.len()..];
+        let digits = format!("{}{}{}", whole, kept, "0".repeat(decimals as usize - kept.len()));
+        let mut atomic: u128 = digits.parse().ok()?;
+        if dropped.bytes().next().is_some_and(|b| b >= b'5') {
+            atomic = atomic.checked_add(1)?;
+        }
+        Some((Self::new(atomic, decimals), dropped.bytes().all(|b| b == b'0')))
+    }
+
+    /// Nearest amount to a coin value already in f64 (manual entry, exchanges, older rows).
+    /// The flag is false when the value had more digits than `decimals` allows.
+    pub fn from_f64(value: f64, decimals: u32) -> Option<(Self, bool)> {
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        // Display prints the shortest decimal that reads back as the same f64, never an exponent
+        Self::round_coins(&value.to_string(), decimals)
+    }
+
+    /// The same amount with `decimals` digits; None when that would drop non-zero digits
+    pub fn rescale(self, decimals: u32) -> Option<Self> {
+        if decimals >= self.decimals {
+            let factor = 10u128.checked_pow(decimals - self.decimals)?;
+            return self.atomic.checked_mul(factor).map(|atomic| Self::new(atomic, decimals));
+        }
+        let factor = 10u128.checked_pow(self.decimals - decimals)?;
+        (self.atomic % factor == 0).then(|| Self::new(self.atomic / factor, decimals))
+    }
+
+    /// Whole coins, without trailing zeros ("0.29999999", "2")
+    pub fn to_coins_string(&self) -> String {
+        let digits = format!("{:0>width$}", self.atomic, width = self.decimals as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - self.decimals as usize);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() { whole.to_string() } else { format!("{}.{}", whole, fraction) }
+    }
+
+    /// Closest f64, parsed from the exact decimal (no division rounding)
+    pub fn to_f64(&self) -> f64 {
+        self.to_coins_string().parse().unwrap_or(0.0)
+    }
+}
+
+pub type ProviderFuture<'a> = Pin<Box<dyn Future<Output = Result<Amount, ProviderError>> + Send + 'a>>;
+
+/// One API able to return the balance of an address
+pub trait BalanceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a>;
+}
+
+/// Base URLs of every source, so that tests can point them at a local mock server
+#[derive(Debug, Clone)]
+pub struct ProviderConfig {
+    pub blockstream: String,
+    pub blockcypher: String,
+    pub blockchair: String,
+    pub bitcoin_com: String,
+    pub etherscan: String,
+    /// `chainid` parameter of the Etherscan requests (V2); None on the V1 endpoint
+    pub etherscan_chain_id: Option<u64>,
+    pub blockscout_etc: String,
+    pub routescan_avax: String,
+    pub polkadot_sidecar: String,
+    pub subscan: String,
+    pub nearblocks: String,
+    pub koios: String,
+    pub blockfrost: String,
+    /// Koios bearer token (`koios_api_key`); anonymous tier when None
+    pub koios_api_key: Option<String>,
+    /// Blockfrost `project_id` (`blockfrost_project_id`); Blockfrost is skipped when None
+    pub blockfrost_project_id: Option<String>,
+    pub qtum_info: String,
+    pub eth_rpcs: Vec<String>,
+    pub erc20_rpcs: Vec<String>,
+    pub etc_rpcs: Vec<String>,
+    pub avax_rpcs: Vec<String>,
+    pub near_rpcs: Vec<String>,
+    pub xrpl_rpcs: Vec<String>,
+    pub solana_rpcs: Vec<String>,
+    /// The wallet's own node (`node_url`), tried before every public source
+    pub custom_node: Option<String>,
+}
+
+fn urls(list: &[&str]) -> Vec<String> {
+    list.iter().map(|u| u.to_string()).collect()
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            blockstream: "https://blockstream.info/api".to_string(),
+            blockcypher: "https://api.blockcypher.com/v1".to_string(),
+            blockchair: "https://api.blockchair.com".to_string(),
+            bitcoin_com: "https://rest1.biggestfan.net/v2".to_string(),
+            etherscan: ETHERSCAN_V2_URL.to_string(),
+            etherscan_chain_id: Some(ETHEREUM_CHAIN_ID),
+            blockscout_etc: "https://blockscout.com/etc/mainnet/api".to_string(),
+            routescan_avax: "https://api.routescan.io/v2/network/mainnet/evm/43114/etherscan/api".to_string(),
+            polkadot_sidecar: "https://polkadot-asset-hub-public-sidecar.parity-chains.parity.io".to_string(),
+            subscan: "https://polkadot.api.subscan.io".to_string(),
+            nearblocks: "https://api.nearblocks.io/v1".to_string(),
+            koios: "https://api.koios.rest/api/v1".to_string(),
+            blockfrost: "https://cardano-mainnet.blockfrost.io/api/v0".to_string(),
+            koios_api_key: None,
+            blockfrost_project_id: None,
+            qtum_info: "https://qtum.info/api".to_string(),
+            eth_rpcs: urls(&["https://eth.llamarpc.com", "https://ethereum-rpc.publicnode.com", "https://rpc.ankr.com/eth"]),
+            erc20_rpcs: urls(&["https://ethereum-rpc.publicnode.com", "https://eth.llamarpc.com", "https://rpc.ankr.com/eth"]),
+            etc_rpcs: urls(&["https://etc.rivet.link", "https://geth-de.etc-network.info", "https://besu-de.etc-network.info"]),
+            avax_rpcs: urls(&["https://api.avax.network/ext/bc/C/rpc", "https://avalanche-c-chain-rpc.publicnode.com"]),
+            near_rpcs: urls(&["https://rpc.mainnet.near.org", "https://rpc.fastnear.com", "https://near.lava.build"]),
+            xrpl_rpcs: urls(&["https://s1.ripple.com:51234/", "https://xrplcluster.com/"]),
+            solana_rpcs: urls(&["https://api.mainnet-beta.solana.com", "https://solana-rpc.publicnode.com"]),
+            custom_node: None,
+        }
+    }
+}
+
+impl ProviderConfig {
+    /// Etherscan requests on the endpoint `api` answers for the configured key
+    pub fn with_etherscan(mut self, api: EtherscanApi) -> Self {
+        (self.etherscan, self.etherscan_chain_id) = match api {
+            EtherscanApi::V2 => (ETHERSCAN_V2_URL.to_string(), Some(ETHEREUM_CHAIN_ID)),
+            EtherscanApi::V1 => (ETHERSCAN_V1_URL.to_string(), None),
+        };
+        self
+    }
+
+    /// Cardano keys; empty values mean anonymous Koios and no Blockfrost
+    pub fn with_cardano_keys(mut self, koios_api_key: &str, blockfrost_project_id: &str) -> Self {
+        let non_empty = |v: &str| Some(v.trim().to_string()).filter(|v| !v.is_empty());
+        self.koios_api_key = non_empty(koios_api_key);
+        self.blockfrost_project_id = non_empty(blockfrost_project_id);
+        self
+    }
+
+    /// Balance queries of assets served by JSON-RPC go to `node_url` first
+    pub fn with_node(mut self, node_url: Option<&str>) -> Self {
+        self.custom_node = node_url.map(str::trim).filter(|u| !u.is_empty()).map(str::to_string);
+        self
+    }
+}
+
+/// Assets whose balance a custom node can answer: EVM chains (`eth_getBalance` / `eth_call`)
+/// and Solana (`getBalance`)
+pub fn supports_custom_node(asset: &str) -> bool {
+    matches!(asset, "eth" | "etc" | "avax" | "link" | "uni" | "aave" | "sol")
+}
+
+/// `eth_chainId` expected from a node serving `asset`
+pub fn evm_chain_id(asset: &str) -> Option<u64> {
+    match asset {
+        "eth" | "link" | "uni" | "aave" => Some(ETHEREUM_CHAIN_ID),
+        "etc" => Some(61),
+        "avax" => Some(43114),
+        _ => None,
+    }
+}
+
+/// Sends with retry (identical in-flight requests coalesced) and decodes a 2xx JSON body
+async fn read_json<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, ProviderError> {
+    let value = crate::http::fetch_json(request).await.map_err(|e| match e {
+        FetchError::Network(e) => ProviderError::Network(e),
+        FetchError::Status(status) => ProviderError::Status(status.as_u16()),
+        FetchError::Parse(e) => ProviderError::Parse(e),
+    })?;
+    serde_json::from_value(value).map_err(|e| ProviderError::Parse(e.to_string()))
+}
+
+/// GET through the Blockchair/Etherscan TTL cache and rate limiter
+async fn cached_json(client: &reqwest::Client, url: &str, force_refresh: bool) -> Result<serde_json::Value, ProviderError> {
+    crate::provider_health::get_json(client, url, force_refresh).await.map_err(ProviderError::Network)
+}
+
+/// Smallest units given as an integer string or a JSON number
+fn as_atomic(value: Option<&serde_json::Value>, decimals: u32) -> Option<Amount> {
+    match value? {
+        serde_json::Value::String(s) => Amount::parse_atomic(s, decimals),
+        serde_json::Value::Number(n) => match n.as_u64() {
+            Some(units) => Some(Amount::new(units as u128, decimals)),
+            // Large values may come as floats: already rounded by the API
+            None => n.as_f64().filter(|f| f.is_finite() && *f >= 0.0).map(|f| Amount::new(f.round() as u128, decimals)),
+        },
+        _ => None,
+    }
+}
+
+/// `0x…` quantity of a JSON-RPC result
+fn hex_quantity(result: Option<&serde_json::Value>) -> Option<u128> {
+    let hex = result?.as_str()?.trim_start_matches("0x");
+    if hex.is_empty() { return None; }
+    u128::from_str_radix(hex, 16).ok()
+}
+
+//
+// UTXO CHAINS
+//
+
+#[derive(Debug, Deserialize)]
+struct BlockstreamUtxo {
+    value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockcypherAddress {
+    balance: Option<u64>,
+    final_balance: Option<u64>,
+}
+
+/// Sum of the UTXOs (Esplora API)
+pub struct BlockstreamProvider {
+    pub base: String,
+}
+
+impl BalanceProvider for BlockstreamProvider {
+    fn name(&self) -> &'static str { "blockstream" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/address/{}/utxo", self.base, address);
+            let utxos: Vec<BlockstreamUtxo> = read_json(client.get(&url)).await?;
+            Ok(Amount::new(utxos.iter().map(|u| u.value as u128).sum(), SATS_DECIMALS))
+        })
+    }
+}
+
+pub struct BlockcypherProvider {
+    pub base: String,
+    /// `btc/main`, `ltc/main`…
+    pub chain: &'static str,
+}
+
+impl BalanceProvider for BlockcypherProvider {
+    fn name(&self) -> &'static str { "blockcypher" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/{}/addrs/{}/balance", self.base, self.chain, address);
+            let data: BlockcypherAddress = read_json(client.get(&url)).await?;
+            let sats = data.final_balance.or(data.balance).ok_or(ProviderError::Missing)?;
+            Ok(Amount::new(sats as u128, SATS_DECIMALS))
+        })
+    }
+}
+
+pub struct BlockchairProvider {
+    pub base: String,
+    /// `bitcoin`, `ethereum/classic`…
+    pub chain: &'static str,
+    /// Decimals of the smallest unit
+    pub decimals: u32,
+    /// Bypass the response cache
+    pub force_refresh: bool,
+}
+
+impl BalanceProvider for BlockchairProvider {
+    fn name(&self) -> &'static str { "blockchair" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/{}/dashboards/address/{}", self.base, self.chain, address);
+            let raw = cached_json(client, &url, self.force_refresh).await?;
+            // The dashboard is keyed by the address as Blockchair normalized it
+            let data = raw.get("data").and_then(|d| d.as_object()).ok_or(ProviderError::Missing)?;
+            for addr_data in data.values() {
+                if let Some(balance) = as_atomic(addr_data.get("address").and_then(|a| a.get("balance")), self.decimals) {
+                    return Ok(balance);
+                }
+            }
+            Err(ProviderError::Missing)
+        })
+    }
+}
+
+/// bitcoin.com REST (BCH, legacy addresses too); balance already in BCH
+pub struct BitcoinComProvider {
+    pub base: String,
+}
+
+impl BalanceProvider for BitcoinComProvider {
+    fn name(&self) -> &'static str { "bitcoin.com" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/address/details/{}", self.base, address);
+            let data: serde_json::Value = read_json(client.get(&url)).await?;
+            data.get("balance").and_then(|b| b.as_f64()).and_then(|b| Amount::from_f64(b, SATS_DECIMALS)).map(|(b, _)| b)
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// qtum.info; balance is a decimal string in QTUM
+pub struct QtumInfoProvider {
+    pub base: String,
+}
+
+impl BalanceProvider for QtumInfoProvider {
+    fn name(&self) -> &'static str { "qtum.info" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/address/{}", self.base, address);
+            let data: serde_json::Value = read_json(client.get(&url)).await?;
+            data.get("balance").and_then(|b| b.as_str()).and_then(|b| Amount::parse_coins(b, SATS_DECIMALS)).ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+//
+// EVM CHAINS
+//
+
+/// Etherscan's multichain endpoint: one key for every chain of ETHERSCAN_CHAINS
+pub const ETHERSCAN_V2_URL: &str = "https://api.etherscan.io/v2/api";
+/// Deprecated per-chain endpoint (Ethereum only), kept for the keys V2 refuses
+pub const ETHERSCAN_V1_URL: &str = "https://api.etherscan.io/api";
+pub const ETHEREUM_CHAIN_ID: u64 = 1;
+
+/// Chains served by Etherscan V2 with the same key
+pub const ETHERSCAN_CHAINS: &[(&str, u64)] = &[
+    ("ethereum", ETHEREUM_CHAIN_ID),
+    ("arbitrum", 42161),
+    ("optimism", 10),
+    ("polygon", 137),
+    ("base", 8453),
+];
+
+pub fn etherscan_chain_id(chain: &str) -> Option<u64> {
+    ETHERSCAN_CHAINS.iter().find(|(name, _)| *name == chain).map(|(_, id)| *id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EtherscanApi {
+    V1,
+    V2,
+}
+
+impl EtherscanApi {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EtherscanApi::V1 => "v1",
+            EtherscanApi::V2 => "v2",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "v1" => Some(EtherscanApi::V1),
+            "v2" => Some(EtherscanApi::V2),
+            _ => None,
+        }
+    }
+
+    /// `query` (module=…&action=…) on `chain_id`; None on V1 for chains other than Ethereum
+    pub fn url(self, chain_id: u64, query: &str, api_key: &str) -> Option<String> {
+        match self {
+            EtherscanApi::V2 => Some(etherscan_url(ETHERSCAN_V2_URL, Some(chain_id), query, api_key)),
+            EtherscanApi::V1 => (chain_id == ETHEREUM_CHAIN_ID).then(|| etherscan_url(ETHERSCAN_V1_URL, None, query, api_key)),
+        }
+    }
+}
+
+fn etherscan_url(base: &str, chain_id: Option<u64>, query: &str, api_key: &str) -> String {
+    // Several keys configured: each request takes the next one in rotation
+    let api_key = crate::provider_health::next_etherscan_key(api_key);
+    match chain_id {
+        Some(id) => format!("{}?chainid={}&{}&apikey={}", base, id, query, api_key),
+        None => format!("{}?{}&apikey={}", base, query, api_key),
+    }
+}
+
+/// Etherscan `balance` / `tokenbalance` (API key required)
+pub struct EtherscanProvider {
+    pub base: String,
+    pub chain_id: Option<u64>,
+    pub api_key: String,
+    /// ERC-20 contract, None for the native balance
+    pub contract: Option<&'static str>,
+    pub force_refresh: bool,
+}
+
+impl BalanceProvider for EtherscanProvider {
+    fn name(&self) -> &'static str { "etherscan" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let query = match self.contract {
+                Some(contract) => format!("module=account&action=tokenbalance&contractaddress={}&address={}&tag=latest", contract, address),
+                None => format!("module=account&action=balance&address={}&tag=latest", address),
+            };
+            let url = etherscan_url(&self.base, self.chain_id, &query, &self.api_key);
+            let data = cached_json(client, &url, self.force_refresh).await?;
+            if data.get("status").and_then(|s| s.as_str()).unwrap_or("0") != "1" {
+                return Err(ProviderError::Missing);
+            }
+            Ok(as_atomic(data.get("result"), WEI_DECIMALS).unwrap_or(Amount::new(0, WEI_DECIMALS)))
+        })
+    }
+}
+
+/// Etherscan-compatible explorers without key (Blockscout, Routescan); result in wei
+pub struct ExplorerApiProvider {
+    pub name: &'static str,
+    pub base: String,
+}
+
+impl BalanceProvider for ExplorerApiProvider {
+    fn name(&self) -> &'static str { self.name }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}?module=account&action=balance&address={}&tag=latest", self.base, address);
+            let data: serde_json::Value = read_json(client.get(&url)).await?;
+            if data.get("status").and_then(|s| s.as_str()) != Some("1") {
+                return Err(ProviderError::Missing);
+            }
+            data.get("result").and_then(|r| r.as_str()).and_then(|r| Amount::parse_atomic(r, WEI_DECIMALS)).ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// JSON-RPC `eth_getBalance`, or `eth_call balanceOf` for an ERC-20 (18 decimals)
+pub struct EvmRpcProvider {
+    pub url: String,
+    pub contract: Option<&'static str>,
+}
+
+impl BalanceProvider for EvmRpcProvider {
+    fn name(&self) -> &'static str { "evm-rpc" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let body = match self.contract {
+                Some(contract) => {
+                    let call_data = format!("0x70a08231000000000000000000000000{}", address.trim_start_matches("0x"));
+                    serde_json::json!({
+                        "jsonrpc": "2.0", "method": "eth_call",
+                        "params": [{ "to": contract, "data": call_data }, "latest"], "id": 1
+                    })
+                }
+                None => serde_json::json!({
+                    "jsonrpc": "2.0", "method": "eth_getBalance",
+                    "params": [address, "latest"], "id": 1
+                }),
+            };
+            let data: serde_json::Value = read_json(client.post(&self.url).json(&body)).await?;
+            let result = data.get("result");
+            // A bare "0x0" from eth_call means the node could not run it: try the next one
+            if self.contract.is_some() && result.and_then(|r| r.as_str()).map(|r| r.trim_start_matches("0x")) == Some("0") {
+                return Err(ProviderError::Missing);
+            }
+            let wei = hex_quantity(result).ok_or(ProviderError::Missing)?;
+            Ok(Amount::new(wei, WEI_DECIMALS))
+        })
+    }
+}
+
+//
+// AUTRES CHAÎNES
+//
+
+/// Polkadot: Blockchair raw account, in planck
+pub struct PolkadotBlockchairProvider {
+    pub base: String,
+    pub force_refresh: bool,
+}
+
+impl BalanceProvider for PolkadotBlockchairProvider {
+    fn name(&self) -> &'static str { "blockchair" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/polkadot/raw/address/{}", self.base, address);
+            let data = cached_json(client, &url, self.force_refresh).await?;
+            let balance = data.get("data").and_then(|d| d.get(address)).and_then(|a| a.get("account")).and_then(|a| a.get("balance"));
+            as_atomic(balance, PLANCK_DECIMALS).ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// Polkadot Asset Hub sidecar (balances live there since Nov 2025)
+pub struct PolkadotSidecarProvider {
+    pub base: String,
+}
+
+impl BalanceProvider for PolkadotSidecarProvider {
+    fn name(&self) -> &'static str { "sidecar" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/accounts/{}/balance-info", self.base, address);
+            let data: serde_json::Value = read_json(client.get(&url).header("Accept", "application/json")).await?;
+            data.get("free").and_then(|f| f.as_str()).and_then(|f| Amount::parse_atomic(f, PLANCK_DECIMALS))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// Subscan account tokens; the native DOT balance is already in DOT
+pub struct SubscanProvider {
+    pub base: String,
+}
+
+impl BalanceProvider for SubscanProvider {
+    fn name(&self) -> &'static str { "subscan" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/api/scan/account/tokens", self.base);
+            let body = serde_json::json!({ "address": address });
+            let data: serde_json::Value = read_json(client.post(&url).json(&body)).await?;
+            data.get("data").and_then(|d| d.get("native")).and_then(|n| n.as_array())
+                .and_then(|tokens| tokens.iter()
+                    .filter(|t| t.get("symbol").and_then(|s| s.as_str()) == Some("DOT"))
+                    .find_map(|t| t.get("balance").and_then(|b| b.as_str()).and_then(|b| Amount::parse_coins(b, PLANCK_DECIMALS))))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// NEAR JSON-RPC `view_account`
+pub struct NearRpcProvider {
+    pub url: String,
+}
+
+impl BalanceProvider for NearRpcProvider {
+    fn name(&self) -> &'static str { "near-rpc" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "janus",
+                "method": "query",
+                "params": { "request_type": "view_account", "finality": "final", "account_id": address }
+            });
+            let data: serde_json::Value = read_json(client.post(&self.url).json(&body)).await?;
+            data.get("result").and_then(|r| r.get("amount")).and_then(|a| a.as_str()).and_then(|a| Amount::parse_atomic(a, YOCTO_DECIMALS))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+pub struct NearBlocksProvider {
+    pub base: String,
+}
+
+impl BalanceProvider for NearBlocksProvider {
+    fn name(&self) -> &'static str { "nearblocks" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/account/{}", self.base, address);
+            let data: serde_json::Value = read_json(client.get(&url)).await?;
+            data.get("account").and_then(|a| a.as_array()).and_then(|a| a.first())
+                .and_then(|a| a.get("amount")).and_then(|a| a.as_str()).and_then(|a| Amount::parse_atomic(a, YOCTO_DECIMALS))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// Reward (stake) address: its balance covers every delegated payment address plus rewards
+pub fn is_stake_address(address: &str) -> bool {
+    address.to_ascii_lowercase().starts_with("stake1")
+}
+
+/// Cardano: Koios `address_info`, or `account_info` for a stake address (free tier without key)
+pub struct KoiosProvider {
+    pub base: String,
+    pub api_key: Option<String>,
+}
+
+impl BalanceProvider for KoiosProvider {
+    fn name(&self) -> &'static str { "koios" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let (endpoint, body, field) = if is_stake_address(address) {
+                ("account_info", serde_json::json!({ "_stake_addresses": [address] }), "total_balance")
+            } else {
+                ("address_info", serde_json::json!({ "_addresses": [address] }), "balance")
+            };
+            let mut request = client.post(format!("{}/{}", self.base, endpoint)).json(&body);
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+            let data: serde_json::Value = read_json(request).await?;
+            data.as_array().and_then(|a| a.first())
+                .and_then(|a| a.get(field)).and_then(|b| b.as_str()).and_then(|b| Amount::parse_atomic(b, LOVELACE_DECIMALS))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// Cardano: Blockfrost, with the user's project id
+pub struct BlockfrostProvider {
+    pub base: String,
+    pub project_id: String,
+}
+
+impl BalanceProvider for BlockfrostProvider {
+    fn name(&self) -> &'static str { "blockfrost" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            if is_stake_address(address) {
+                let url = format!("{}/accounts/{}", self.base, address);
+                let data: serde_json::Value = read_json(client.get(&url).header("project_id", &self.project_id)).await?;
+                return data.get("controlled_amount").and_then(|q| q.as_str()).and_then(|q| Amount::parse_atomic(q, LOVELACE_DECIMALS))
+                    .ok_or(ProviderError::Missing);
+            }
+            let url = format!("{}/addresses/{}", self.base, address);
+            let data: serde_json::Value = read_json(client.get(&url).header("project_id", &self.project_id)).await?;
+            data.get("amount").and_then(|a| a.as_array())
+                .and_then(|amounts| amounts.iter()
+                    .filter(|a| a.get("unit").and_then(|u| u.as_str()) == Some("lovelace"))
+                    .find_map(|a| a.get("quantity").and_then(|q| q.as_str()).and_then(|q| Amount::parse_atomic(q, LOVELACE_DECIMALS))))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// XRP Ledger JSON-RPC `account_info`
+pub struct XrplRpcProvider {
+    pub url: String,
+}
+
+impl BalanceProvider for XrplRpcProvider {
+    fn name(&self) -> &'static str { "xrpl" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "method": "account_info",
+                "params": [{ "account": address, "strict": true, "ledger_index": "current" }]
+            });
+            let data: serde_json::Value = read_json(client.post(&self.url).json(&body)).await?;
+            data.get("result").and_then(|r| r.get("account_data")).and_then(|a| a.get("Balance"))
+                .and_then(|b| b.as_str()).and_then(|b| Amount::parse_atomic(b, DROPS_DECIMALS))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+/// Solana JSON-RPC `getBalance`
+pub struct SolanaRpcProvider {
+    pub url: String,
+}
+
+impl BalanceProvider for SolanaRpcProvider {
+    fn name(&self) -> &'static str { "solana-rpc" }
+
+    fn fetch<'a>(&'a self, client: &'a reqwest::Client, address: &'a str) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "getBalance", "params": [address] });
+            let data: serde_json::Value = read_json(client.post(&self.url).json(&body)).await?;
+            data.get("result").and_then(|r| r.get("value")).and_then(|v| v.as_u64())
+                .map(|lamports| Amount::new(lamports as u128, LAMPORTS_DECIMALS))
+                .ok_or(ProviderError::Missing)
+        })
+    }
+}
+
+//
+// CHAÎNES DE FALLBACK
+//
+
+/// Providers tried in order; the first balance wins
+pub struct BalanceChain {
+    providers: Vec<Box<dyn BalanceProvider>>,
+    not_found: String,
+}
+
+impl BalanceChain {
+    fn new(not_found: &str) -> Self {
+        Self { providers: Vec::new(), not_found: not_found.to_string() }
+    }
+
+    fn then(mut self, provider: impl BalanceProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    fn first(mut self, provider: impl BalanceProvider + 'static) -> Self {
+        self.providers.insert(0, Box::new(provider));
+        self
+    }
+
+    fn then_each<P: BalanceProvider + 'static>(self, urls: &[String], provider: impl Fn(String) -> P) -> Self {
+        urls.iter().cloned().map(provider).fold(self, Self::then)
+    }
+
+    /// Balance in whole coins; see fetch_amount
+    pub async fn fetch(&self, client: &reqwest::Client, address: &str) -> Result<f64, JanusError> {
+        self.fetch_amount(client, address).await.map(|amount| amount.to_f64())
+    }
+
+    /// Every source rate-limited → `RateLimited`, every source unreachable → `Network`
+    /// (first provider named), otherwise the address is taken as unknown → `NotFound`.
+    pub async fn fetch_amount(&self, client: &reqwest::Client, address: &str) -> Result<Amount, JanusError> {
+        let mut failures = Vec::new();
+        for provider in &self.providers {
+            match provider.fetch(client, address).await {
+                Ok(balance) => return Ok(balance),
+                Err(e) => {
+                    log::debug!("[BALANCE] {} failed: {}", provider.name(), e);
+                    failures.push((provider.name(), e));
+                }
+            }
+        }
+        let rate_limited = |e: &ProviderError| matches!(e, ProviderError::Status(429))
+            || matches!(e, ProviderError::Network(msg) if msg.contains("HTTP 429"));
+        let unreachable = |e: &ProviderError| matches!(e, ProviderError::Network(_) | ProviderError::Status(500..=599));
+        match failures.first() {
+            Some(_) if failures.iter().all(|(_, e)| rate_limited(e)) => Err(JanusError::RateLimited { retry_after: RATE_LIMIT_RETRY_SECS }),
+            Some((name, e)) if failures.iter().all(|(_, e)| unreachable(e)) => {
+                Err(JanusError::Network { provider: name.to_string(), message: e.to_string() })
+            }
+            _ => Err(JanusError::NotFound(self.not_found.clone())),
+        }
+    }
+}
+
+pub fn token_contract(token: &str) -> Option<&'static str> {
+    match token {
+        "link" => Some("0x514910771af9ca656af840dff83e8264ecf986ca"),
+        "uni" => Some("0x1f9840a85d5af5bf1d1762f925bdaddc4201f984"),
+        "aave" => Some("0x7fc66500c84a76ad7e9c93437bfc5ac33e2ddae9"),
+        _ => None,
+    }
+}
+
+/// Fallback ladder of `asset`; None when no public API is wired for it.
+/// Etherscan is skipped when `etherscan_key` is empty; `force_refresh` bypasses the
+/// Blockchair/Etherscan response cache.
+pub fn chain_for(asset: &str, config: &ProviderConfig, etherscan_key: &str, force_refresh: bool) -> Option<BalanceChain> {
+    let blockchair = |chain: &'static str, decimals: u32| BlockchairProvider { base: config.blockchair.clone(), chain, decimals, force_refresh };
+    let blockcypher = |chain: &'static str| BlockcypherProvider { base: config.blockcypher.clone(), chain };
+    let etherscan = |contract: Option<&'static str>| {
+        (!etherscan_key.is_empty()).then(|| EtherscanProvider { base: config.etherscan.clone(), chain_id: config.etherscan_chain_id, api_key: etherscan_key.to_string(), contract, force_refresh })
+    };
+    let chain = match asset {
+        "btc" => BalanceChain::new("Balance BTC introuvable (3 APIs testées) — vérifiez l'adresse")
+            .then(BlockstreamProvider { base: config.blockstream.clone() })
+            .then(blockcypher("btc/main"))
+            .then(blockchair("bitcoin", SATS_DECIMALS)),
+        "bch" => BalanceChain::new("Balance BCH non trouvée — essayez le format cashaddr (ex: bitcoincash:qq...)")
+            .then(blockchair("bitcoin-cash", SATS_DECIMALS))
+            .then(BitcoinComProvider { base: config.bitcoin_com.clone() })
+            .then(blockcypher("bch/main")),
+        "ltc" => BalanceChain::new("Balance LTC non trouvée — vérifiez le format d'adresse")
+            .then(blockcypher("ltc/main"))
+            .then(blockchair("litecoin", SATS_DECIMALS)),
+        "doge" => BalanceChain::new("Balance DOGE non trouvée — vérifiez l'adresse")
+            .then(blockcypher("doge/main"))
+            .then(blockchair("dogecoin", SATS_DECIMALS)),
+        "dash" => BalanceChain::new("Balance DASH non trouvée — vérifiez l'adresse")
+            .then(blockchair("dash", SATS_DECIMALS)),
+        "qtum" => BalanceChain::new("Balance QTUM non trouvée — vérifiez l'adresse")
+            .then(QtumInfoProvider { base: config.qtum_info.clone() })
+            .then(blockchair("qtum", SATS_DECIMALS)),
+        "eth" => {
+            let mut chain = BalanceChain::new("Balance ETH non trouvée — vérifiez l'adresse et la clé Etherscan");
+            if let Some(provider) = etherscan(None) { chain = chain.then(provider); }
+            chain.then_each(&config.eth_rpcs, |url| EvmRpcProvider { url, contract: None })
+        }
+        "etc" => BalanceChain::new("Balance ETC non trouvée — adresse 0x... requise")
+            .then_each(&config.etc_rpcs, |url| EvmRpcProvider { url, contract: None })
+            .then(ExplorerApiProvider { name: "blockscout", base: config.blockscout_etc.clone() })
+            .then(blockchair("ethereum/classic", WEI_DECIMALS)),
+        "avax" => BalanceChain::new("Balance AVAX non trouvée — utilisez une adresse C-Chain (0x...)")
+            .then_each(&config.avax_rpcs, |url| EvmRpcProvider { url, contract: None })
+            .then(ExplorerApiProvider { name: "routescan", base: config.routescan_avax.clone() }),
+        "link" | "uni" | "aave" => {
+            let contract = token_contract(asset);
+            let mut chain = BalanceChain::new(&format!("Balance {} non trouvée", asset.to_uppercase()));
+            if let Some(provider) = etherscan(contract) { chain = chain.then(provider); }
+            chain.then_each(&config.erc20_rpcs, |url| EvmRpcProvider { url, contract })
+        }
+        "dot" => BalanceChain::new("Balance DOT non trouvée — vérifiez l'adresse Polkadot (format SS58)")
+            .then(PolkadotBlockchairProvider { base: config.blockchair.clone(), force_refresh })
+            .then(PolkadotSidecarProvider { base: config.polkadot_sidecar.clone() })
+            .then(SubscanProvider { base: config.subscan.clone() }),
+        "near" => BalanceChain::new("Balance NEAR non trouvée — utilisez le nom de compte (ex: moncompte.near)")
+            .then_each(&config.near_rpcs, |url| NearRpcProvider { url })
+            .then(NearBlocksProvider { base: config.nearblocks.clone() }),
+        "ada" => {
+            let chain = BalanceChain::new("Balance ADA non trouvée — vérifiez l'adresse (format addr1... ou stake1...)")
+                .then(KoiosProvider { base: config.koios.clone(), api_key: config.koios_api_key.clone() });
+            match &config.blockfrost_project_id {
+                Some(project_id) => chain.then(BlockfrostProvider { base: config.blockfrost.clone(), project_id: project_id.clone() }),
+                None => chain,
+            }
+        }
+        "xrp" => BalanceChain::new("Balance XRP non trouvée — vérifiez l'adresse (format r...)")
+            .then_each(&config.xrpl_rpcs, |url| XrplRpcProvider { url }),
+        "sol" => BalanceChain::new("Balance SOL non trouvée — vérifiez la clé publique Solana")
+            .then_each(&config.solana_rpcs, |url| SolanaRpcProvider { url }),
+        _ => return None,
+    };
+    let chain = match config.custom_node.clone() {
+        Some(url) if asset == "sol" => chain.first(SolanaRpcProvider { url }),
+        Some(url) if evm_chain_id(asset).is_some() => chain.first(EvmRpcProvider { url, contract: token_contract(asset) }),
+        _ => chain,
+    };
+    Some(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Every source on the mock server, one RPC endpoint per list
+    fn mock_config(server: &MockServer) -> ProviderConfig {
+        let uri = server.uri();
+        let rpc = |name: &str| vec![format!("{}/{}", uri, name)];
+        ProviderConfig {
+            blockstream: format!("{}/blockstream", uri),
+            blockcypher: format!("{}/blockcypher", uri),
+            blockchair: format!("{}/blockchair", uri),
+            bitcoin_com: format!("{}/bitcoin-com", uri),
+            etherscan: format!("{}/etherscan", uri),
+            etherscan_chain_id: Some(ETHEREUM_CHAIN_ID),
+            blockscout_etc: format!("{}/blockscout", uri),
+            routescan_avax: format!("{}/routescan", uri),
+            polkadot_sidecar: format!("{}/sidecar", uri),
+            subscan: format!("{}/subscan", uri),
+            nearblocks: format!("{}/nearblocks", uri),
+            koios: format!("{}/koios", uri),
+            blockfrost: format!("{}/blockfrost", uri),
+            koios_api_key: None,
+            blockfrost_project_id: None,
+            qtum_info: format!("{}/qtum", uri),
+            eth_rpcs: rpc("eth-rpc"),
+            erc20_rpcs: rpc("erc20-rpc"),
+            etc_rpcs: rpc("etc-rpc"),
+            avax_rpcs: rpc("avax-rpc"),
+            near_rpcs: rpc("near-rpc"),
+            xrpl_rpcs: rpc("xrpl"),
+            solana_rpcs: rpc("solana-rpc"),
+            custom_node: None,
+        }
+    }
+
+    const BTC_ADDRESS: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const ETH_ADDRESS: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[tokio::test]
+    async fn btc_sums_utxos_then_falls_back() {
+        let server = MockServer::start().await;
+        let chain = chain_for("btc", &mock_config(&server), "", false).unwrap();
+        let client = reqwest::Client::new();
+
+        Mock::given(method("GET")).and(path(format!("/blockstream/address/{}/utxo", BTC_ADDRESS)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{ "value": 150_000_000u64 }, { "value": 25_000 }])))
+            .up_to_n_times(1)
+            .mount(&server).await;
+        assert_eq!(chain.fetch(&client, BTC_ADDRESS).await, Ok(1.50025));
+
+        // Blockstream gone, Blockcypher without final_balance, Blockchair answers
+        Mock::given(method("GET")).and(path(format!("/blockchair/bitcoin/dashboards/address/{}", BTC_ADDRESS)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { BTC_ADDRESS: { "address": { "balance": 42_000_000 } } }
+            })))
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path(format!("/blockcypher/btc/main/addrs/{}/balance", BTC_ADDRESS)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "address": BTC_ADDRESS })))
+            .mount(&server).await;
+        assert_eq!(chain.fetch(&client, BTC_ADDRESS).await, Ok(0.42));
+    }
+
+    #[tokio::test]
+    async fn btc_reports_not_found_when_every_source_fails() {
+        let server = MockServer::start().await;
+        let chain = chain_for("btc", &mock_config(&server), "", false).unwrap();
+        assert_eq!(
+            chain.fetch(&reqwest::Client::new(), BTC_ADDRESS).await.unwrap_err(),
+            JanusError::NotFound("Balance BTC introuvable (3 APIs testées) — vérifiez l'adresse".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn eth_prefers_etherscan_when_keyed() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/etherscan"))
+            .and(query_param("chainid", "1")).and(query_param("action", "balance")).and(query_param("apikey", "KEY"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "1", "result": "2500000000000000000" })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/eth-rpc")).and(body_partial_json(serde_json::json!({ "method": "eth_getBalance" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0xde0b6b3a7640000" })))
+            .mount(&server).await;
+        let client = reqwest::Client::new();
+        let config = mock_config(&server);
+
+        assert_eq!(chain_for("eth", &config, "KEY", false).unwrap().fetch(&client, ETH_ADDRESS).await, Ok(2.5));
+        // No key: straight to the RPC
+        assert_eq!(chain_for("eth", &config, "", false).unwrap().fetch(&client, ETH_ADDRESS).await, Ok(1.0));
+    }
+
+    #[tokio::test]
+    async fn wallet_node_is_tried_before_public_sources() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/etherscan"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "1", "result": "2500000000000000000" })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/my-node")).and(body_partial_json(serde_json::json!({ "method": "eth_getBalance" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x4563918244f40000" })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/my-node")).and(body_partial_json(serde_json::json!({ "method": "getBalance" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": 1_500_000_000u64 } })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/solana-rpc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": 0 } })))
+            .mount(&server).await;
+        let client = reqwest::Client::new();
+        let node = format!("{}/my-node", server.uri());
+        let config = mock_config(&server).with_node(Some(&node));
+
+        // Ahead of Etherscan, even with a key
+        assert_eq!(chain_for("eth", &config, "KEY", false).unwrap().fetch(&client, ETH_ADDRESS).await, Ok(5.0));
+        assert_eq!(chain_for("sol", &config, "", false).unwrap().fetch(&client, "So1anaAddress").await, Ok(1.5));
+        // Node down: the public sources still answer
+        let down = mock_config(&server).with_node(Some("http://127.0.0.1:9"));
+        assert_eq!(chain_for("eth", &down, "KEY", false).unwrap().fetch(&client, ETH_ADDRESS).await, Ok(2.5));
+        assert_eq!(mock_config(&server).with_node(Some("  ")).custom_node, None);
+        assert!(!supports_custom_node("btc"));
+    }
+
+    #[test]
+    fn etherscan_v2_multiplexes_chain_ids() {
+        assert_eq!(
+            EtherscanApi::V2.url(42161, "module=proxy&action=eth_blockNumber", "KEY").as_deref(),
+            Some("https://api.etherscan.io/v2/api?chainid=42161&module=proxy&action=eth_blockNumber&apikey=KEY")
+        );
+        assert_eq!(
+            EtherscanApi::V1.url(ETHEREUM_CHAIN_ID, "module=proxy&action=eth_blockNumber", "KEY").as_deref(),
+            Some("https://api.etherscan.io/api?module=proxy&action=eth_blockNumber&apikey=KEY")
+        );
+        // V1 keys only reach Ethereum mainnet
+        assert_eq!(EtherscanApi::V1.url(137, "module=proxy&action=eth_blockNumber", "KEY"), None);
+        assert_eq!(EtherscanApi::parse(EtherscanApi::V1.as_str()), Some(EtherscanApi::V1));
+        assert_eq!(EtherscanApi::parse("v3"), None);
+        assert_eq!(etherscan_chain_id("base"), Some(8453));
+        assert_eq!(etherscan_chain_id("bsc"), None);
+
+        let v1 = ProviderConfig::default().with_etherscan(EtherscanApi::V1);
+        assert_eq!((v1.etherscan.as_str(), v1.etherscan_chain_id), (ETHERSCAN_V1_URL, None));
+        let v2 = v1.with_etherscan(EtherscanApi::V2);
+        assert_eq!((v2.etherscan.as_str(), v2.etherscan_chain_id), (ETHERSCAN_V2_URL, Some(ETHEREUM_CHAIN_ID)));
+    }
+
+    #[tokio::test]
+    async fn erc20_balance_of_via_rpc() {
+        let server = MockServer::start().await;
+        let call_data = format!("0x70a08231000000000000000000000000{}", &ETH_ADDRESS[2..]);
+        Mock::given(method("GET")).and(path("/etherscan"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/erc20-rpc"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "eth_call",
+                "params": [{ "to": token_contract("link").unwrap(), "data": call_data }, "latest"]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": "0x00000000000000000000000000000000000000000000000029a2241af62c0000"
+            })))
+            .mount(&server).await;
+        let chain = chain_for("link", &mock_config(&server), "KEY", false).unwrap();
+        assert_eq!(chain.fetch(&reqwest::Client::new(), ETH_ADDRESS).await, Ok(3.0));
+    }
+
+    #[tokio::test]
+    async fn erc20_not_found_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).and(path("/erc20-rpc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x0" })))
+            .mount(&server).await;
+        let chain = chain_for("uni", &mock_config(&server), "", false).unwrap();
+        assert_eq!(chain.fetch(&reqwest::Client::new(), ETH_ADDRESS).await.unwrap_err(), JanusError::NotFound("Balance UNI non trouvée".into()));
+    }
+
+    #[tokio::test]
+    async fn ada_sends_cardano_keys_and_reads_stake_accounts() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).and(path("/koios/account_info")).and(header("authorization", "Bearer KOIOS"))
+            .and(body_partial_json(serde_json::json!({ "_stake_addresses": ["stake1uabc"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{ "total_balance": "12500000" }])))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/koios/address_info"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path("/blockfrost/addresses/addr1abc")).and(header("project_id", "mainnetXYZ"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "amount": [{ "unit": "lovelace", "quantity": "3000000" }] })))
+            .mount(&server).await;
+        let client = reqwest::Client::new();
+        let config = mock_config(&server).with_cardano_keys("KOIOS", "mainnetXYZ");
+
+        // Stake address: delegated balance + rewards from account_info
+        assert_eq!(chain_for("ada", &config, "", false).unwrap().fetch(&client, "stake1uabc").await, Ok(12.5));
+        // Koios refuses: Blockfrost with the project id
+        assert_eq!(chain_for("ada", &config, "", false).unwrap().fetch(&client, "addr1abc").await, Ok(3.0));
+        // No project id: Blockfrost is not tried
+        let anonymous = mock_config(&server).with_cardano_keys("", " ");
+        assert!(chain_for("ada", &anonymous, "", false).unwrap().fetch(&client, "addr1abc").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn quota_and_outage_are_not_reported_as_unknown_address() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/blockchair/dash/dashboards/address/Xabc"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server).await;
+        let chain = chain_for("dash", &mock_config(&server), "", false).unwrap();
+        assert_eq!(chain.fetch(&reqwest::Client::new(), "Xabc").await.unwrap_err().code(), "rate_limited");
+
+        // Nothing listening: every source unreachable
+        let config = ProviderConfig { blockchair: "http://127.0.0.1:9".to_string(), ..mock_config(&server) };
+        let err = chain_for("dash", &config, "", false).unwrap().fetch(&reqwest::Client::new(), "Xabc").await.unwrap_err();
+        assert!(matches!(err, JanusError::Network { ref provider, .. } if provider == "blockchair"), "{:?}", err);
+    }
+
+    #[test]
+    fn unsupported_assets_have_no_chain() {
+        let config = ProviderConfig::default();
+        assert!(chain_for("xmr", &config, "", false).is_none());
+        assert!(chain_for("pivx", &config, "", false).is_none());
+        assert!(chain_for("btc", &config, "", false).is_some());
+    }
+
+    #[test]
+    fn amounts_stay_exact_in_atomic_units() {
+        let sats = Amount::parse_atomic("29999999", 8).unwrap();
+        assert_eq!(sats.to_coins_string(), "0.29999999");
+        assert_eq!(sats.to_f64(), 0.29999999);
+        assert_eq!(Amount::parse_coins("0.29999999", 8), Some(sats));
+        assert_eq!(Amount::parse_coins("0.299999991", 8), None);
+        assert_eq!(Amount::parse_atomic("12a", 8), None);
+        // Past f64 precision: every wei is kept
+        let wei = Amount::parse_atomic("1234567890123456789012", 18).unwrap();
+        assert_eq!(wei.to_coins_string(), "1234.567890123456789012");
+        assert_eq!(Amount::new(2_000_000, 6).to_coins_string(), "2");
+        assert_eq!(Amount::new(5, 8).to_coins_string(), "0.00000005");
+
+        // f64 inputs: exact when the value fits the decimals, rounded and flagged otherwise
+        assert_eq!(Amount::from_f64(0.1, 18), Some((Amount::new(100_000_000_000_000_000, 18), true)));
+        assert_eq!(Amount::from_f64(0.123456789, 8), Some((Amount::new(12_345_679, 8), false)));
+        assert_eq!(Amount::from_f64(-1.0, 8), None);
+        assert_eq!(sats.rescale(10), Some(Amount::new(2_999_999_900, 10)));
+        assert_eq!(Amount::new(5, 8).rescale(6), None);
+    }
+}
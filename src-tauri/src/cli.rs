@@ -0,0 +1,397 @@
+use crate::{AssetAllocation, PortfolioSummary};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::Serialize;
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+//
+// MODE LIGNE DE COMMANDE (--summary / --json)
+//
+
+/// Tauri identifier: the GUI keeps its data in `<data_local_dir>/com.janus.monitor`
+const APP_IDENTIFIER: &str = "com.janus.monitor";
+
+const USAGE: &str = "\
+Usage : janus-monitor --summary | --json [options]
+
+  --summary              Résumé du portefeuille en tableau
+  --json                 Résumé du portefeuille en JSON
+  --refresh              Actualise les soldes on-chain avant le résumé
+  --pin <PIN>            Déverrouille le profil actif (détail par portefeuille, --refresh)
+  --stale-after <secs>   Âge au-delà duquel un solde est à actualiser (défaut 86400)
+  --data-dir <dossier>   Dossier de données (défaut : celui de l'application)
+  -h, --help             Affiche cette aide
+
+Base chiffrée (SQLCipher) : passphrase dans JANUS_DB_PASSPHRASE.
+Codes de sortie : 0 ok, 1 erreur, 2 usage, 3 actualisation partielle.";
+
+const EXIT_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARTIAL: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+struct CliArgs {
+    format: OutputFormat,
+    refresh: bool,
+    pin: Option<Zeroizing<String>>,
+    stale_after_secs: i64,
+    data_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+enum CliCommand {
+    Summary(CliArgs),
+    Help,
+}
+
+/// None when the arguments don't ask for the CLI (normal GUI start: the OS or the
+/// bundler may pass arguments of their own, those are left alone).
+fn parse_args(args: &[String]) -> Result<Option<CliCommand>, String> {
+    let wanted = args.iter().any(|a| matches!(a.as_str(), "--summary" | "--json" | "--help" | "-h"));
+    if !wanted {
+        return Ok(None);
+    }
+    let mut format = None;
+    let mut refresh = false;
+    let mut pin = None;
+    let mut stale_after_secs = crate::DEFAULT_STALE_AFTER_SECS;
+    let mut data_dir = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().cloned().ok_or_else(|| format!("{} attend une valeur", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Some(CliCommand::Help)),
+            "--summary" | "--json" => {
+                let requested = if arg == "--json" { OutputFormat::Json } else { OutputFormat::Table };
+                if format.is_some_and(|f| f != requested) {
+                    return Err("--summary et --json sont exclusifs".to_string());
+                }
+                format = Some(requested);
+            }
+            "--refresh" => refresh = true,
+            "--pin" => pin = Some(Zeroizing::new(value("--pin")?)),
+            "--stale-after" => {
+                stale_after_secs = value("--stale-after")?
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|s| *s >= 0)
+                    .ok_or("--stale-after attend un nombre de secondes")?;
+            }
+            "--data-dir" => data_dir = Some(PathBuf::from(value("--data-dir")?)),
+            other => return Err(format!("Argument inconnu : {}", other)),
+        }
+    }
+    let format = format.ok_or("--summary ou --json requis")?;
+    Ok(Some(CliCommand::Summary(CliArgs { format, refresh, pin, stale_after_secs, data_dir })))
+}
+
+/// Runs the CLI when the arguments ask for it and returns the process exit code,
+/// None to start the GUI. Called first thing in `run()`.
+///
+/// Release builds on Windows use the GUI subsystem: there the output only shows when redirected.
+pub fn try_run(args: &[String]) -> Option<i32> {
+    let command = match parse_args(args) {
+        Ok(command) => command?,
+        Err(e) => {
+            eprintln!("janus-monitor : {}\n\n{}", e, USAGE);
+            return Some(EXIT_USAGE);
+        }
+    };
+    let CliCommand::Summary(args) = command else {
+        println!("{}", USAGE);
+        return Some(0);
+    };
+    Some(match tauri::async_runtime::block_on(summary(&args)) {
+        Ok(report) => {
+            match args.format {
+                OutputFormat::Json => match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("janus-monitor : {}", e);
+                        return Some(EXIT_ERROR);
+                    }
+                },
+                OutputFormat::Table => print!("{}", render_table(&report)),
+            }
+            for error in &report.refresh_errors {
+                eprintln!("janus-monitor : {}", error);
+            }
+            if report.refresh_errors.is_empty() { 0 } else { EXIT_PARTIAL }
+        }
+        Err(e) => {
+            eprintln!("janus-monitor : {}", e);
+            EXIT_ERROR
+        }
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct CliReport {
+    profile: String,
+    /// Protected profile read without its PIN: per-asset aggregates only
+    restricted: bool,
+    generated_at: String,
+    total_eur: f64,
+    total_usd: f64,
+    total_btc: f64,
+    assets: Vec<AssetAllocation>,
+    /// Categories, top holdings and stale wallets (None when restricted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<PortfolioSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refreshed_wallets: Option<usize>,
+    refresh_errors: Vec<String>,
+}
+
+async fn summary(args: &CliArgs) -> Result<CliReport, String> {
+    let data_dir = args.data_dir.clone().or_else(default_data_dir).ok_or("Dossier de données introuvable")?;
+    crate::DATA_DIR.set(data_dir.clone()).ok();
+    let db_path = data_dir.join("janus.db");
+    if !db_path.exists() {
+        return Err(format!("Aucune base dans {} — lancez l'application une première fois", data_dir.display()));
+    }
+    if crate::db_encryption_marker().exists() {
+        let passphrase = std::env::var("JANUS_DB_PASSPHRASE")
+            .map_err(|_| "Base chiffrée : définissez JANUS_DB_PASSPHRASE".to_string())?;
+        *crate::DB_PASSPHRASE.lock().map_err(|e| e.to_string())? = Some(Zeroizing::new(passphrase));
+    }
+
+    // Read-only unless balances are written back
+    let conn = if args.refresh {
+        crate::open_db(&db_path).map_err(|e| e.to_string())?
+    } else {
+        open_read_only(&db_path)?
+    };
+    crate::reload_proxy_settings(&conn);
+    crate::reload_http_settings(&conn);
+    crate::reload_etherscan_settings(&conn);
+    crate::restore_price_snapshot(&conn);
+
+    let profile = crate::read_setting(&conn, "active_profile").unwrap_or_default();
+    let protected = !profile.is_empty() && crate::profile_has_auth(&conn, &profile);
+    let key = match &args.pin {
+        Some(pin) if protected => {
+            pin_security_init(&db_path);
+            unlock(&conn, &profile, pin)?
+        }
+        Some(_) => return Err("Le profil actif n'a pas de PIN".to_string()),
+        None => None,
+    };
+    crate::reload_cardano_settings(&conn, key.as_deref().map(Vec::as_slice));
+    let restricted = protected && args.pin.is_none();
+    if restricted && args.refresh {
+        return Err("--refresh sur un profil protégé demande --pin".to_string());
+    }
+
+    let (refreshed_wallets, refresh_errors) = if args.refresh {
+        let (count, errors) = refresh_balances(&conn, key.as_deref().map(Vec::as_slice)).await?;
+        (Some(count), errors)
+    } else {
+        (None, Vec::new())
+    };
+
+    let prices = crate::refresh_prices().await?;
+    let assets = crate::allocate_by_asset(&conn, &prices)?;
+    let total_eur: f64 = assets.iter().map(|a| a.value_eur).sum();
+    let (total_usd, total_btc) = crate::eur_to_usd_btc(&prices, total_eur);
+    let now = chrono::Utc::now();
+    let summary = if restricted {
+        None
+    } else {
+        Some(crate::summarize_portfolio(&conn, &prices, now.timestamp(), args.stale_after_secs)?)
+    };
+    Ok(CliReport {
+        profile,
+        restricted,
+        generated_at: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        total_eur,
+        total_usd,
+        total_btc,
+        assets,
+        summary,
+        refreshed_wallets,
+        refresh_errors,
+    })
+}
+
+fn default_data_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join(APP_IDENTIFIER))
+}
+
+fn open_read_only(path: &std::path::Path) -> Result<Connection, String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| e.to_string())?;
+    let passphrase = crate::DB_PASSPHRASE.lock().ok().and_then(|p| p.clone());
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase.as_str()).map_err(|e| e.to_string())?;
+    }
+    // With a wrong SQLCipher key the first read fails with "file is not a database"
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("Lecture de la base impossible : {}", e))?;
+    Ok(conn)
+}
+
+/// Failed PINs count against the same limiter as the GUI
+fn pin_security_init(db_path: &std::path::Path) {
+    if let Err(e) = crate::pin_security::init_persistence(db_path) {
+        log::warn!("[CLI] Rate limit state not loaded: {}", e);
+    }
+}
+
+/// Session key of `profile` from its PIN. Profiles that also need a password or
+/// 2FA can't be opened from the command line.
+fn unlock(conn: &Connection, profile: &str, pin: &str) -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+    crate::pin_security::check_rate_limit(profile).map_err(|s| crate::rate_limit_error(s).to_string())?;
+    let (pin_hash, password_hash, totp_enabled) = conn.query_row(
+        "SELECT pin_hash, password_hash, COALESCE(totp_enabled, 0) FROM profile_security WHERE profile_name = ?1",
+        params![profile],
+        |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?)),
+    )
+    .map_err(|_| "Profile security not configured".to_string())?;
+    if password_hash.is_some_and(|h| !h.is_empty()) || totp_enabled == 1 {
+        return Err("Profil protégé par mot de passe ou 2FA : seul le résumé par actif est disponible sans --pin".to_string());
+    }
+    let pin_hash = pin_hash.filter(|h| !h.is_empty()).ok_or("Le profil actif n'a pas de PIN")?;
+    // Legacy hashes are only migrated by the GUI (the CLI may hold a read-only connection)
+    let valid = if crate::pin_security::is_legacy_sha256_hash(&pin_hash) {
+        crate::sha256_hex(pin) == pin_hash
+    } else {
+        crate::pin_security::verify_pin(pin, &pin_hash)?
+    };
+    if !valid {
+        crate::pin_security::record_failed_attempt(profile)?;
+        return Err("PIN incorrect".to_string());
+    }
+    crate::pin_security::record_successful_attempt(profile)?;
+
+    let Some(salt) = crate::profile_salt(conn, profile) else { return Ok(None) };
+    let salt_bytes = hex::decode(&salt).map_err(|e| format!("Invalid salt: {}", e))?;
+    // Data still under the pre-Argon2 KDF until the GUI's first unlock re-encrypts it
+    if crate::read_setting(conn, &format!("session_kdf:{}", profile)).as_deref() != Some("argon2id") {
+        return Ok(Some(crate::legacy_session_key(pin, &salt_bytes)));
+    }
+    let key = crate::pin_security::derive_key(pin, &salt_bytes)?;
+    Ok(Some(Zeroizing::new(key.to_vec())))
+}
+
+/// Fetches the balance of every active wallet with an address and stores it, like the
+/// dashboard's refresh. Returns the number of wallets updated and one message per failure.
+async fn refresh_balances(conn: &Connection, key: Option<&[u8]>) -> Result<(usize, Vec<String>), String> {
+    let etherscan_key = crate::read_secret_setting(conn, "etherscan_api_key", key);
+    let wallets: Vec<(i64, String, String, String, Option<String>)> = conn
+        .prepare("SELECT id, name, LOWER(asset), address, node_url FROM wallets WHERE archived = 0 AND COALESCE(address, '') != '' ORDER BY id")
+        .and_then(|mut stmt| stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?.collect())
+        .map_err(|e| e.to_string())?;
+
+    let mut updated = 0;
+    let mut errors = Vec::new();
+    for (id, name, asset, address, node_url) in wallets {
+        // Monero balances are entered by hand
+        if asset == "xmr" {
+            continue;
+        }
+        let Some(address) = crate::open_stored_address(address, key) else {
+            errors.push(format!("{} : adresse illisible", name));
+            continue;
+        };
+        let node_url = node_url.filter(|u| !u.is_empty()).and_then(|u| crate::open_stored_address(u, key));
+        match crate::fetch_chain_balance(&asset, address, &etherscan_key, node_url.as_deref(), false).await {
+            Ok(balance) => {
+                // No monitor here to vouch for a change: any alert is kept for the GUI
+                crate::store_fetched_balance(conn, id, balance, false)?;
+                updated += 1;
+            }
+            Err(e) => errors.push(format!("{} ({}) : {}", name, asset.to_uppercase(), e)),
+        }
+    }
+    Ok((updated, errors))
+}
+
+fn render_table(report: &CliReport) -> String {
+    let mut out = String::new();
+    let profile = if report.profile.is_empty() { "—" } else { report.profile.as_str() };
+    out.push_str(&format!("JANUS Monitor — profil {} — {}\n", profile, report.generated_at));
+    if report.restricted {
+        out.push_str("Profil verrouillé : totaux par actif uniquement (--pin pour le détail)\n");
+    }
+    out.push_str(&format!("\n{:<8} {:>20} {:>14} {:>7}\n", "Actif", "Solde", "Valeur EUR", "%"));
+    for a in &report.assets {
+        let value = if a.priced { format!("{:.2}", a.value_eur) } else { "sans prix".to_string() };
+        out.push_str(&format!("{:<8} {:>20.8} {:>14} {:>6.1}%\n", a.asset.to_uppercase(), a.balance, value, a.percent));
+    }
+    out.push_str(&format!(
+        "\nTotal : {:.2} € · {:.2} $ · {:.8} BTC\n",
+        report.total_eur, report.total_usd, report.total_btc
+    ));
+    if let Some(count) = report.refreshed_wallets {
+        out.push_str(&format!("Soldes actualisés : {}\n", count));
+    }
+    let Some(summary) = &report.summary else { return out };
+
+    out.push_str("\nCatégories\n");
+    for c in summary.categories.iter().filter(|c| c.wallet_count > 0) {
+        out.push_str(&format!("  {:<24} {:>14.2} € {:>6.1}%\n", c.category_name, c.value_eur, c.percent));
+    }
+    if !summary.top_holdings.is_empty() {
+        out.push_str("\nPrincipales positions\n");
+        for h in &summary.top_holdings {
+            out.push_str(&format!(
+                "  {:<24} {:<6} {:>14.2} € {:>6.1}%\n",
+                h.wallet_name, h.asset.to_uppercase(), h.value_eur, h.percent
+            ));
+        }
+    }
+    if summary.stale_wallets > 0 {
+        out.push_str(&format!("\nPortefeuilles à actualiser : {}\n", summary.stale_wallets));
+    }
+    if !summary.unpriced_assets.is_empty() {
+        out.push_str(&format!("Sans prix : {}\n", summary.unpriced_assets.join(", ")));
+    }
+    if summary.unread_alerts > 0 {
+        out.push_str(&format!("Alertes non lues : {}\n", summary.unread_alerts));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn cli_arguments() {
+        assert_eq!(parse_args(&args(&[])), Ok(None));
+        assert_eq!(parse_args(&args(&["-psn_0_12345"])), Ok(None));
+        assert_eq!(parse_args(&args(&["--refresh", "--help"])), Ok(Some(CliCommand::Help)));
+
+        let Ok(Some(CliCommand::Summary(parsed))) = parse_args(&args(&["--json", "--refresh", "--pin", "1234", "--stale-after", "3600"])) else {
+            panic!("expected a summary command");
+        };
+        assert_eq!(parsed.format, OutputFormat::Json);
+        assert!(parsed.refresh);
+        assert_eq!(parsed.pin.as_deref().map(String::as_str), Some("1234"));
+        assert_eq!(parsed.stale_after_secs, 3600);
+        assert_eq!(parsed.data_dir, None);
+
+        let Ok(Some(CliCommand::Summary(parsed))) = parse_args(&args(&["--summary", "--data-dir", "/tmp/janus"])) else {
+            panic!("expected a summary command");
+        };
+        assert_eq!(parsed.format, OutputFormat::Table);
+        assert_eq!(parsed.stale_after_secs, crate::DEFAULT_STALE_AFTER_SECS);
+        assert_eq!(parsed.data_dir, Some(PathBuf::from("/tmp/janus")));
+
+        assert!(parse_args(&args(&["--summary", "--json"])).is_err());
+        assert!(parse_args(&args(&["--json", "--pin"])).is_err());
+        assert!(parse_args(&args(&["--json", "--stale-after", "-5"])).is_err());
+        assert!(parse_args(&args(&["--json", "--verbose"])).is_err());
+    }
+}
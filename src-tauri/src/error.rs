@@ -0,0 +1,143 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+//
+// ERREURS DES COMMANDES
+//
+
+/// Error of the migrated Tauri commands. Serialized as
+/// `{ code, message, user_message, retry_after, provider }`: the frontend keys its
+/// behavior off `code`, shows `user_message` and uses the structured fields
+/// (countdowns, provider name) instead of parsing text.
+///
+/// Commands still returning `String` keep working: `From<String>` wraps their
+/// messages as `Internal`, and `From<JanusError> for String` goes the other way.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum JanusError {
+    /// Bad input; the text is meant for the user
+    #[error("validation failed: {0}")]
+    Validation(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// Wrong PIN, password or 2FA code
+    #[error("authentication failed")]
+    AuthFailed,
+    /// Too many attempts, or a provider quota; retry after `retry_after` seconds
+    #[error("rate limited, retry in {retry_after}s")]
+    RateLimited { retry_after: u64 },
+    /// Profile locked out after too many failed attempts
+    #[error("locked, retry in {retry_after}s")]
+    Locked { retry_after: u64 },
+    #[error("network error ({provider}): {message}")]
+    Network { provider: String, message: String },
+    #[error("crypto error: {0}")]
+    Crypto(String),
+    #[error("database error: {0}")]
+    Database(String),
+    /// Legacy message from code not migrated yet, already worded for the user
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl JanusError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            JanusError::Validation(_) => "validation",
+            JanusError::NotFound(_) => "not_found",
+            JanusError::AuthFailed => "auth_failed",
+            JanusError::RateLimited { .. } => "rate_limited",
+            JanusError::Locked { .. } => "locked",
+            JanusError::Network { .. } => "network",
+            JanusError::Crypto(_) => "crypto",
+            JanusError::Database(_) => "database",
+            JanusError::Internal(_) => "internal",
+        }
+    }
+
+    /// French wording for the UI
+    pub fn user_message(&self) -> Option<String> {
+        match self {
+            JanusError::Validation(msg) | JanusError::NotFound(msg) | JanusError::Internal(msg) => Some(msg.clone()),
+            JanusError::AuthFailed => Some("Authentification échouée".to_string()),
+            JanusError::RateLimited { retry_after } => Some(format!("Trop de tentatives. Réessayez dans {} secondes.", retry_after)),
+            JanusError::Locked { retry_after } => Some(format!("Profil verrouillé. Réessayez dans {} secondes.", retry_after)),
+            JanusError::Network { provider, .. } => Some(format!("{} injoignable — vérifiez la connexion ou le proxy", provider)),
+            JanusError::Crypto(_) => Some("Erreur de chiffrement".to_string()),
+            JanusError::Database(_) => Some("Erreur de base de données".to_string()),
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            JanusError::RateLimited { retry_after } | JanusError::Locked { retry_after } => Some(*retry_after),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for JanusError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("JanusError", 5)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("user_message", &self.user_message())?;
+        s.serialize_field("retry_after", &self.retry_after())?;
+        s.serialize_field("provider", &match self {
+            JanusError::Network { provider, .. } => Some(provider.as_str()),
+            _ => None,
+        })?;
+        s.end()
+    }
+}
+
+impl From<String> for JanusError {
+    fn from(msg: String) -> Self {
+        JanusError::Internal(msg)
+    }
+}
+
+impl From<&str> for JanusError {
+    fn from(msg: &str) -> Self {
+        JanusError::Internal(msg.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for JanusError {
+    fn from(e: rusqlite::Error) -> Self {
+        JanusError::Database(e.to_string())
+    }
+}
+
+/// For the commands still on `Result<_, String>`
+impl From<JanusError> for String {
+    fn from(e: JanusError) -> Self {
+        e.user_message().unwrap_or_else(|| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_structured_fields() {
+        let json = serde_json::to_value(JanusError::Locked { retry_after: 42 }).unwrap();
+        assert_eq!(json["code"], "locked");
+        assert_eq!(json["retry_after"], 42);
+        assert_eq!(json["user_message"], "Profil verrouillé. Réessayez dans 42 secondes.");
+        assert!(json["provider"].is_null());
+
+        let json = serde_json::to_value(JanusError::Network { provider: "blockstream".into(), message: "timeout".into() }).unwrap();
+        assert_eq!(json["code"], "network");
+        assert_eq!(json["message"], "network error (blockstream): timeout");
+        assert_eq!(json["provider"], "blockstream");
+        assert!(json["retry_after"].is_null());
+    }
+
+    #[test]
+    fn legacy_strings_round_trip() {
+        let e = JanusError::from("Adresse vide".to_string());
+        assert_eq!(e.code(), "internal");
+        assert_eq!(String::from(e), "Adresse vide");
+        assert_eq!(String::from(JanusError::RateLimited { retry_after: 5 }), "Trop de tentatives. Réessayez dans 5 secondes.");
+    }
+}
@@ -0,0 +1,267 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+//
+// SOLDES DES COMPTES D'EXCHANGE (LECTURE SEULE)
+//
+
+const COINBASE_API_VERSION: &str = "2024-01-01";
+/// Coinbase pages hold at most 300 accounts; stop following `next_uri` after this many pages
+const COINBASE_MAX_PAGES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exchange {
+    Kraken,
+    Coinbase,
+}
+
+impl Exchange {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "kraken" => Some(Exchange::Kraken),
+            "coinbase" => Some(Exchange::Coinbase),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Exchange::Kraken => "kraken",
+            Exchange::Coinbase => "coinbase",
+        }
+    }
+}
+
+/// API base URLs, so that tests can point them at a local mock server
+#[derive(Debug, Clone)]
+pub struct ExchangeConfig {
+    pub kraken: String,
+    pub coinbase: String,
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            kraken: "https://api.kraken.com".to_string(),
+            coinbase: "https://api.coinbase.com".to_string(),
+        }
+    }
+}
+
+/// Decrypted key pair, only alive for the duration of a request
+pub struct Credentials {
+    pub api_key: Zeroizing<String>,
+    pub api_secret: Zeroizing<String>,
+}
+
+/// Kraken: base64(HMAC-SHA512(base64_decode(secret), path || SHA256(nonce || post_data)))
+pub fn kraken_signature(path: &str, nonce: u64, post_data: &str, secret_b64: &str) -> Result<String, String> {
+    let secret = Zeroizing::new(
+        base64::engine::general_purpose::STANDARD.decode(secret_b64.trim())
+            .map_err(|_| "Secret Kraken invalide (base64 attendu)".to_string())?,
+    );
+    let digest = Sha256::digest(format!("{}{}", nonce, post_data).as_bytes());
+    let mut mac = Hmac::<Sha512>::new_from_slice(&secret).map_err(|e| e.to_string())?;
+    mac.update(path.as_bytes());
+    mac.update(&digest);
+    Ok(base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+/// Coinbase (API key auth): hex(HMAC-SHA256(secret, timestamp || method || path || body))
+pub fn coinbase_signature(secret: &str, timestamp: i64, method: &str, path: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}{}{}{}", timestamp, method, path, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Kraken rejects a nonce lower than or equal to the previous one
+static LAST_NONCE: Lazy<Mutex<u64>> = Lazy::new(|| Mutex::new(0));
+
+fn next_nonce() -> u64 {
+    let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+    let mut last = LAST_NONCE.lock().unwrap_or_else(|e| e.into_inner());
+    *last = now.max(*last + 1);
+    *last
+}
+
+/// Kraken asset code → lowercase ticker: `XXBT` → btc, `XXDG` → doge, `ZEUR` → eur, `DOT.S` → dot
+pub fn kraken_asset(code: &str) -> String {
+    let code = code.split('.').next().unwrap_or(code).to_uppercase();
+    let code = match code.as_str() {
+        "XXBT" | "XBT" => "BTC",
+        "XXDG" | "XDG" => "DOGE",
+        "ETH2" => "ETH",
+        c if c.len() == 4 && (c.starts_with('X') || c.starts_with('Z')) => &c[1..],
+        c => c,
+    };
+    code.to_lowercase()
+}
+
+/// Signed POST on a Kraken private endpoint. Never retried: a replayed nonce is rejected anyway.
+async fn kraken_private(client: &reqwest::Client, config: &ExchangeConfig, creds: &Credentials, path: &str, params: &str) -> Result<serde_json::Value, String> {
+    let nonce = next_nonce();
+    let post_data = if params.is_empty() { format!("nonce={}", nonce) } else { format!("nonce={}&{}", nonce, params) };
+    let signature = kraken_signature(path, nonce, &post_data, &creds.api_secret)?;
+    let request = client.post(format!("{}{}", config.kraken, path))
+        .header("API-Key", creds.api_key.as_str())
+        .header("API-Sign", signature)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(post_data);
+    let response = crate::http::send_once(request).await
+        .map_err(|e| format!("kraken: {}", e.without_url()))?;
+    let body: serde_json::Value = response.json().await.map_err(|e| format!("kraken: {}", e.without_url()))?;
+    let errors: Vec<&str> = body["error"].as_array().map(|e| e.iter().filter_map(|v| v.as_str()).collect()).unwrap_or_default();
+    if !errors.is_empty() {
+        return Err(format!("kraken: {}", errors.join(", ")));
+    }
+    Ok(body["result"].clone())
+}
+
+/// Signed GET on the Coinbase v2 API; `path` includes the query string
+async fn coinbase_get(client: &reqwest::Client, config: &ExchangeConfig, creds: &Credentials, path: &str) -> Result<serde_json::Value, String> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = coinbase_signature(&creds.api_secret, timestamp, "GET", path, "");
+    let request = client.get(format!("{}{}", config.coinbase, path))
+        .header("CB-ACCESS-KEY", creds.api_key.as_str())
+        .header("CB-ACCESS-SIGN", signature)
+        .header("CB-ACCESS-TIMESTAMP", timestamp.to_string())
+        .header("CB-VERSION", COINBASE_API_VERSION);
+    let response = crate::http::send_once(request).await
+        .map_err(|e| format!("coinbase: {}", e.without_url()))?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.map_err(|e| format!("coinbase: {}", e.without_url()))?;
+    if !status.is_success() {
+        let detail = body["errors"][0]["message"].as_str().unwrap_or("réponse inattendue");
+        return Err(format!("coinbase: HTTP {} ({})", status.as_u16(), detail));
+    }
+    Ok(body)
+}
+
+fn parse_amount(value: &serde_json::Value) -> Option<f64> {
+    value.as_str().and_then(|s| s.parse::<f64>().ok()).or_else(|| value.as_f64()).filter(|a| a.is_finite())
+}
+
+/// Non-zero balances per lowercase ticker (staked / earn variants summed with the spot balance)
+pub async fn fetch_balances(client: &reqwest::Client, config: &ExchangeConfig, exchange: Exchange, creds: &Credentials) -> Result<BTreeMap<String, f64>, String> {
+    let mut balances: BTreeMap<String, f64> = BTreeMap::new();
+    match exchange {
+        Exchange::Kraken => {
+            let result = kraken_private(client, config, creds, "/0/private/Balance", "").await?;
+            for (code, amount) in result.as_object().into_iter().flatten() {
+                if let Some(amount) = parse_amount(amount) {
+                    *balances.entry(kraken_asset(code)).or_default() += amount;
+                }
+            }
+        }
+        Exchange::Coinbase => {
+            let mut path = Some("/v2/accounts?limit=100".to_string());
+            for _ in 0..COINBASE_MAX_PAGES {
+                let Some(current) = path.take() else { break };
+                let body = coinbase_get(client, config, creds, &current).await?;
+                for account in body["data"].as_array().into_iter().flatten() {
+                    let code = account["balance"]["currency"].as_str();
+                    if let (Some(code), Some(amount)) = (code, parse_amount(&account["balance"]["amount"])) {
+                        *balances.entry(code.to_lowercase()).or_default() += amount;
+                    }
+                }
+                path = body["pagination"]["next_uri"].as_str().filter(|u| u.starts_with("/v2/")).map(str::to_string);
+            }
+        }
+    }
+    balances.retain(|_, amount| *amount > 0.0);
+    Ok(balances)
+}
+
+/// Permissions beyond reading balances held by the key (empty = read-only)
+pub async fn risky_permissions(client: &reqwest::Client, config: &ExchangeConfig, exchange: Exchange, creds: &Credentials) -> Result<Vec<String>, String> {
+    match exchange {
+        Exchange::Kraken => {
+            // Kraken has no permission listing: probe endpoints that need them. `validate=true`
+            // only checks the order, it is never submitted.
+            let denied = |r: &Result<serde_json::Value, String>| matches!(r, Err(e) if e.contains("Permission denied"));
+            let mut risky = Vec::new();
+            let order = kraken_private(client, config, creds, "/0/private/AddOrder", "ordertype=market&type=buy&volume=0.0001&pair=XBTUSD&validate=true").await;
+            if !denied(&order) {
+                risky.push("trade".to_string());
+            }
+            let withdraw = kraken_private(client, config, creds, "/0/private/WithdrawMethods", "").await;
+            if !denied(&withdraw) {
+                risky.push("withdraw".to_string());
+            }
+            Ok(risky)
+        }
+        Exchange::Coinbase => {
+            let body = coinbase_get(client, config, creds, "/v2/user/auth").await?;
+            Ok(body["data"]["scopes"].as_array().into_iter().flatten()
+                .filter_map(|s| s.as_str())
+                .filter(|s| !s.ends_with(":read"))
+                .map(str::to_string)
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn signatures_match_reference_vectors() {
+        // Example from Kraken's REST authentication documentation
+        let signature = kraken_signature(
+            "/0/private/AddOrder",
+            1616492376594,
+            "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25",
+            "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==",
+        ).unwrap();
+        assert_eq!(signature, "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ==");
+        assert!(kraken_signature("/0/private/Balance", 1, "nonce=1", "not base64!").is_err());
+        // Same prehash as Coinbase's reference implementation: timestamp + method + path + body
+        assert_eq!(
+            coinbase_signature("secret", 1616492376, "GET", "/v2/accounts?limit=100", ""),
+            "4d6c2e91b135810f2c11431b570ed8ae905e5e8be020d3573ffe538a2486481e",
+        );
+    }
+
+    #[test]
+    fn kraken_asset_codes() {
+        for (code, asset) in [("XXBT", "btc"), ("XBT.M", "btc"), ("XETH", "eth"), ("ETH2.S", "eth"), ("XXDG", "doge"), ("ZEUR", "eur"), ("DOT.S", "dot"), ("XXMR", "xmr"), ("ADA", "ada"), ("LINK", "link"), ("PAXG", "paxg")] {
+            assert_eq!(kraken_asset(code), asset, "{}", code);
+        }
+        assert!(next_nonce() < next_nonce());
+    }
+
+    #[tokio::test]
+    async fn kraken_balances_are_signed_and_summed() {
+        let server = MockServer::start().await;
+        let config = ExchangeConfig { kraken: server.uri(), coinbase: server.uri() };
+        let creds = Credentials {
+            api_key: Zeroizing::new("key".to_string()),
+            api_secret: Zeroizing::new(base64::engine::general_purpose::STANDARD.encode(b"secret")),
+        };
+        Mock::given(method("POST")).and(path("/0/private/Balance")).and(header("API-Key", "key")).and(header_exists("API-Sign"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "error": [],
+                "result": { "XXBT": "0.5000000000", "XBT.M": "0.25", "ZEUR": "10.00", "DOT": "0.0000000000" }
+            })))
+            .mount(&server).await;
+        let balances = fetch_balances(&reqwest::Client::new(), &config, Exchange::Kraken, &creds).await.unwrap();
+        assert_eq!(balances, BTreeMap::from([("btc".to_string(), 0.75), ("eur".to_string(), 10.0)]));
+
+        Mock::given(method("POST")).and(path("/0/private/AddOrder"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "error": ["EGeneral:Permission denied"] })))
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/0/private/WithdrawMethods"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "error": [], "result": [] })))
+            .mount(&server).await;
+        let risky = risky_permissions(&reqwest::Client::new(), &config, Exchange::Kraken, &creds).await.unwrap();
+        assert_eq!(risky, vec!["withdraw".to_string()]);
+    }
+}
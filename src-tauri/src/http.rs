@@ -0,0 +1,354 @@
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+//
+// CLIENT HTTP PARTAGÉ
+//
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 15;
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+const TIMEOUT_RANGE_SECS: std::ops::RangeInclusive<u64> = 3..=120;
+const MAX_RETRIES_LIMIT: u32 = 5;
+const BACKOFF_BASE_MS: u64 = 400;
+pub const DEFAULT_USER_AGENT: &str = concat!("janus-monitor/", env!("CARGO_PKG_VERSION"));
+const MAX_USER_AGENT_LEN: usize = 200;
+
+/// `http_timeout_secs` / `http_max_retries` / `http_user_agent`, cached like the proxy
+/// settings: refreshed at startup and by set_setting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSettings {
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub user_agent: Cow<'static, str>,
+}
+
+const DEFAULT_SETTINGS: HttpSettings = HttpSettings {
+    timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+    max_retries: DEFAULT_MAX_RETRIES,
+    user_agent: Cow::Borrowed(DEFAULT_USER_AGENT),
+};
+
+static SETTINGS: Mutex<HttpSettings> = Mutex::new(DEFAULT_SETTINGS);
+
+/// (proxy, self-signed certs allowed, default timeout, user agent)
+type ClientKey = (Option<String>, bool, Duration, String);
+
+/// One pooled client per key: reqwest clients are cheap to clone and share their
+/// connection pool, so TLS sessions are reused across commands.
+static CLIENTS: Lazy<Mutex<HashMap<ClientKey, reqwest::Client>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn parse_timeout_secs(value: &str) -> Result<u64, String> {
+    value.trim().parse::<u64>().ok()
+        .filter(|secs| TIMEOUT_RANGE_SECS.contains(secs))
+        .ok_or_else(|| format!("Délai HTTP invalide : {} à {} secondes", TIMEOUT_RANGE_SECS.start(), TIMEOUT_RANGE_SECS.end()))
+}
+
+pub fn parse_max_retries(value: &str) -> Result<u32, String> {
+    value.trim().parse::<u32>().ok()
+        .filter(|n| *n <= MAX_RETRIES_LIMIT)
+        .ok_or_else(|| format!("Nombre de tentatives invalide : 0 à {}", MAX_RETRIES_LIMIT))
+}
+
+/// Printable ASCII only (it goes into a header); empty means the default
+pub fn parse_user_agent(value: &str) -> Result<Option<String>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if value.len() > MAX_USER_AGENT_LEN || !value.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+        return Err(format!("User-Agent invalide : ASCII imprimable, {} caractères au plus", MAX_USER_AGENT_LEN));
+    }
+    Ok(Some(value.to_string()))
+}
+
+/// Applies the stored settings; missing or invalid values fall back to the defaults.
+pub fn configure(timeout_secs: Option<&str>, max_retries: Option<&str>, user_agent: Option<&str>) {
+    let settings = HttpSettings {
+        timeout: Duration::from_secs(timeout_secs.and_then(|v| parse_timeout_secs(v).ok()).unwrap_or(DEFAULT_TIMEOUT_SECS)),
+        max_retries: max_retries.and_then(|v| parse_max_retries(v).ok()).unwrap_or(DEFAULT_MAX_RETRIES),
+        user_agent: user_agent.and_then(|v| parse_user_agent(v).ok().flatten())
+            .map(Cow::Owned)
+            .unwrap_or(Cow::Borrowed(DEFAULT_USER_AGENT)),
+    };
+    if let Ok(mut cached) = SETTINGS.lock() {
+        *cached = settings;
+    }
+}
+
+pub fn settings() -> HttpSettings {
+    SETTINGS.lock().map(|s| s.clone()).unwrap_or(DEFAULT_SETTINGS)
+}
+
+/// Shared client with the configured default timeout. Requests that need longer
+/// (wallet scans) override it with `RequestBuilder::timeout`.
+pub fn client(proxy: Option<&str>) -> Result<reqwest::Client, String> {
+    client_with(proxy, false)
+}
+
+pub fn client_with(proxy: Option<&str>, accept_invalid_certs: bool) -> Result<reqwest::Client, String> {
+    let HttpSettings { timeout, user_agent, .. } = settings();
+    let key = (proxy.map(str::to_string), accept_invalid_certs, timeout, user_agent.to_string());
+    let mut clients = CLIENTS.lock().map_err(|e| e.to_string())?;
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+    let mut builder = reqwest::Client::builder()
+        .user_agent(user_agent.as_ref())
+        .timeout(timeout)
+        .connect_timeout(timeout.min(Duration::from_secs(10)))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .pool_max_idle_per_host(8)
+        .danger_accept_invalid_certs(accept_invalid_certs);
+    if let Some(url) = proxy {
+        // Loopback nodes (local wallet-rpc, pivxd) stay direct: Tor refuses them and they leak nothing
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| format!("Proxy invalide : {}", e))?
+            .no_proxy(reqwest::NoProxy::from_string("localhost,127.0.0.1,::1"));
+        builder = builder.proxy(proxy);
+    }
+    let client = builder.build().map_err(|e| e.to_string())?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+//
+// RETRY
+//
+
+/// Worth another attempt: timeouts, connection failures and 5xx. Never 4xx.
+fn is_transient_error(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect() || err.is_request()
+}
+
+/// Exponential backoff with up to 50% jitter so that parallel refreshes don't retry in lockstep
+fn backoff(attempt: u32) -> Duration {
+    let base = BACKOFF_BASE_MS << attempt.min(6);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    Duration::from_millis(base + nanos % (base / 2 + 1))
+}
+
+/// Single `send()`, counted with its latency in the per-provider usage (see provider_health).
+/// 4xx/5xx answers count as errors.
+pub async fn send_once(request: reqwest::RequestBuilder) -> Result<reqwest::Response, reqwest::Error> {
+    let started = Instant::now();
+    let result = request.send().await;
+    let (url, ok) = match &result {
+        Ok(response) => (Some(response.url()), !response.status().is_client_error() && !response.status().is_server_error()),
+        Err(e) => (e.url(), false),
+    };
+    if let Some(url) = url {
+        crate::provider_health::record_usage(url, started.elapsed(), ok);
+    }
+    result
+}
+
+async fn send_with_retries(request: reqwest::RequestBuilder, max_retries: u32) -> Result<reqwest::Response, reqwest::Error> {
+    let mut attempt = 0;
+    loop {
+        // Streaming bodies can't be replayed: single attempt
+        let Some(retry) = request.try_clone().filter(|_| attempt < max_retries) else {
+            return send_once(request).await;
+        };
+        match send_once(retry).await {
+            Ok(response) if response.status().is_server_error() => {
+                log::debug!("[HTTP] {} (HTTP {}), retry {}/{}", response.url().path(), response.status(), attempt + 1, max_retries);
+            }
+            Err(e) if is_transient_error(&e) => {
+                log::debug!("[HTTP] {}, retry {}/{}", e, attempt + 1, max_retries);
+            }
+            result => return result,
+        }
+        tokio::time::sleep(backoff(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// `send()` with the configured bounded retry. After the last attempt the response
+/// (5xx included) or error is returned unchanged, so callers keep their status handling.
+pub trait SendRetry {
+    fn send_retry(self) -> impl Future<Output = Result<reqwest::Response, reqwest::Error>> + Send;
+}
+
+impl SendRetry for reqwest::RequestBuilder {
+    fn send_retry(self) -> impl Future<Output = Result<reqwest::Response, reqwest::Error>> + Send {
+        send_with_retries(self, settings().max_retries)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum FetchError {
+    #[error("{0}")]
+    Network(String),
+    #[error("HTTP {0}")]
+    Status(reqwest::StatusCode),
+    #[error("Réponse JSON invalide : {0}")]
+    Parse(String),
+}
+
+async fn read_json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, FetchError> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(FetchError::Status(status));
+    }
+    response.json().await.map_err(|e| FetchError::Parse(e.to_string()))
+}
+
+//
+// REQUÊTES IDENTIQUES EN VOL
+//
+
+type SharedFetch = Shared<BoxFuture<'static, Result<serde_json::Value, FetchError>>>;
+
+/// Requests currently on the wire, by `request_key`. Entries remove themselves
+/// when the response is in, so this never serves stale data.
+static IN_FLIGHT: Lazy<Mutex<HashMap<String, SharedFetch>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Method + normalized URL + body; None for streaming bodies, which can't be replayed
+fn request_key(request: &reqwest::RequestBuilder) -> Option<String> {
+    let request = request.try_clone()?.build().ok()?;
+    let body = request.body().map(|b| b.as_bytes()).unwrap_or(Some(&[]))?;
+    Some(format!("{} {} {}", request.method(), request.url(), String::from_utf8_lossy(body)))
+}
+
+/// Sends with retry and decodes the JSON body. Concurrent identical requests (two
+/// wallets on the same address, prices and monitoring asking for the same tip)
+/// await a single underlying call. Only for idempotent requests.
+pub async fn fetch_json(request: reqwest::RequestBuilder) -> Result<serde_json::Value, FetchError> {
+    let Some(key) = request_key(&request) else {
+        let response = request.send_retry().await.map_err(|e| FetchError::Network(e.to_string()))?;
+        return read_json(response).await;
+    };
+    let shared = {
+        let mut in_flight = IN_FLIGHT.lock().map_err(|e| FetchError::Network(e.to_string()))?;
+        in_flight.entry(key.clone()).or_insert_with(|| {
+            async move {
+                let result = match request.send_retry().await {
+                    Ok(response) => read_json(response).await,
+                    Err(e) => Err(FetchError::Network(e.to_string())),
+                };
+                if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+                    in_flight.remove(&key);
+                }
+                result
+            }
+            .boxed()
+            .shared()
+        }).clone()
+    };
+    shared.await
+}
+
+/// GET + JSON decoding with retry; non-2xx statuses become `HTTP <code>` errors
+pub async fn get_json<T: DeserializeOwned>(client: &reqwest::Client, url: &str) -> Result<T, String> {
+    let value = fetch_json(client.get(url)).await.map_err(|e| e.to_string())?;
+    serde_json::from_value(value).map_err(|e| FetchError::Parse(e.to_string()).to_string())
+}
+
+/// POST of a JSON body + JSON decoding with retry (only for idempotent calls)
+pub async fn post_json<B: Serialize + ?Sized, T: DeserializeOwned>(client: &reqwest::Client, url: &str, body: &B) -> Result<T, String> {
+    let value = fetch_json(client.post(url).json(body)).await.map_err(|e| e.to_string())?;
+    serde_json::from_value(value).map_err(|e| FetchError::Parse(e.to_string()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn settings_are_bounded() {
+        assert_eq!(parse_timeout_secs(" 30 "), Ok(30));
+        assert!(parse_timeout_secs("0").is_err());
+        assert!(parse_timeout_secs("600").is_err());
+        assert_eq!(parse_max_retries("0"), Ok(0));
+        assert!(parse_max_retries("10").is_err());
+        assert!(parse_max_retries("-1").is_err());
+        assert_eq!(parse_user_agent("  "), Ok(None));
+        assert_eq!(parse_user_agent(" my-node/1.0 (ops@example.org) "), Ok(Some("my-node/1.0 (ops@example.org)".to_string())));
+        assert!(parse_user_agent("bad\r\nX-Injected: 1").is_err());
+        assert!(parse_user_agent("ünïcode").is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_then_succeeds() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/price"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server).await;
+        Mock::given(method("GET")).and(path("/price"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "usd": 1.5 })))
+            .expect(1)
+            .mount(&server).await;
+        let client = reqwest::Client::new();
+        let response = send_with_retries(client.get(format!("{}/price", server.uri())), 2).await.unwrap();
+        let body: serde_json::Value = read_json(response).await.unwrap();
+        assert_eq!(body["usd"], 1.5);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(500)).expect(2).mount(&server).await;
+        let response = send_with_retries(reqwest::Client::new().get(server.uri()), 1).await.unwrap();
+        assert_eq!(response.status(), 500);
+        assert_eq!(read_json::<serde_json::Value>(response).await.unwrap_err().to_string(), "HTTP 500 Internal Server Error");
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(429)).expect(1).mount(&server).await;
+        let response = send_with_retries(reqwest::Client::new().post(server.uri()).json(&serde_json::json!({})), 3).await.unwrap();
+        assert_eq!(response.status(), 429);
+    }
+
+    #[tokio::test]
+    async fn concurrent_identical_requests_share_one_call() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/tip"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(870_000).set_delay(Duration::from_millis(200)))
+            .expect(1)
+            .mount(&server).await;
+        Mock::given(method("POST")).and(path("/rpc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "result": "0x1" })))
+            .expect(2)
+            .mount(&server).await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/tip", server.uri());
+        let (a, b) = tokio::join!(get_json::<u64>(&client, &url), get_json::<u64>(&client, &url));
+        assert_eq!((a, b), (Ok(870_000), Ok(870_000)));
+        // Different bodies are different requests
+        let rpc = format!("{}/rpc", server.uri());
+        let (first, second) = (serde_json::json!({ "id": 1 }), serde_json::json!({ "id": 2 }));
+        let (a, b) = tokio::join!(
+            post_json::<_, serde_json::Value>(&client, &rpc, &first),
+            post_json::<_, serde_json::Value>(&client, &rpc, &second),
+        );
+        assert!(a.is_ok() && b.is_ok());
+        // Other tests share the map: only this server's requests must be gone
+        assert!(!IN_FLIGHT.lock().unwrap().keys().any(|key| key.contains(&server.uri())));
+    }
+
+    #[tokio::test]
+    async fn timeouts_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .expect(2)
+            .mount(&server).await;
+        let request = reqwest::Client::new().get(server.uri()).timeout(Duration::from_millis(100));
+        assert!(send_with_retries(request, 1).await.unwrap_err().is_timeout());
+    }
+}
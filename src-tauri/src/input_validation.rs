@@ -37,55 +37,348 @@ pub fn validate_asset(asset: &str) -> Result<(), String> {
     validate_non_empty("Asset", asset, MAX_ASSET_LEN)
 }
 
+/// Assets whose addresses are 0x + 40 hex (EIP-55)
+const ETH_FAMILY: &[&str] = &[
+    "ETH", "ETC", "LINK", "UNI", "AAVE", "MKR", "CRV", "WBTC", "USDT", "USDC",
+    "DAI", "EURC", "RAI", "FRAX", "LUSD", "XAUT", "PAXG", "MATIC", "ARB",
+];
+
+pub fn is_eth_family(asset: &str) -> bool {
+    ETH_FAMILY.contains(&asset.to_uppercase().as_str())
+}
+
 pub fn validate_address(asset: &str, address: &str) -> Result<(), String> {
     if address.is_empty() { return Ok(()); }
     validate_string("Address", address, MAX_ADDRESS_LEN)?;
+    if is_eth_family(asset) { return validate_eth_address(address); }
     match asset.to_uppercase().as_str() {
         "BTC" => validate_btc_address(address),
-        "ETH" | "LINK" | "UNI" | "AAVE" | "MKR" | "CRV" | "WBTC" | "USDT" | "USDC" |
-        "DAI" | "EURC" | "RAI" | "FRAX" | "LUSD" | "XAUT" | "PAXG" | "MATIC" | "ARB" => validate_eth_address(address),
         "XMR" => validate_xmr_address(address),
         "BCH" => validate_bch_address(address),
         "LTC" => validate_ltc_address(address),
+        "DOGE" => validate_doge_address(address),
         "DOT" => validate_dot_address(address),
+        "PIVX" => validate_pivx_address(address),
+        "SOL" => validate_sol_address(address),
+        "ADA" => validate_ada_address(address),
+        "XRP" => validate_xrp_address(address),
+        "NEAR" => validate_near_address(address),
+        "ATOM" => validate_atom_address(address),
         _ => Ok(())
     }
 }
 
+/// Base58Check: 1-byte version + 20-byte hash + 4-byte double-SHA256 checksum
+fn validate_base58check(coin: &str, addr: &str, versions: &[u8]) -> Result<(), String> {
+    let payload = bs58::decode(addr).with_check(None).into_vec().map_err(|e| match e {
+        bs58::decode::Error::InvalidChecksum { .. } => format!("Invalid {} address: checksum mismatch — likely a typo", coin),
+        _ => format!("Invalid {} address: {:.10}... is not base58", coin, addr),
+    })?;
+    if payload.len() != 21 || !versions.contains(&payload[0]) {
+        return Err(format!("Invalid {} address: {:.10}... (wrong version byte or length)", coin, addr));
+    }
+    Ok(())
+}
+
+/// bech32 (witness v0) / bech32m (v1+) segwit address with the coin's human-readable part
+fn validate_segwit(coin: &str, addr: &str, hrp: &str) -> Result<(), String> {
+    use bech32::primitives::decode::SegwitHrpstringError;
+    let (decoded_hrp, _, _) = bech32::segwit::decode(addr).map_err(|e| match e.0 {
+        SegwitHrpstringError::Checksum(_) => format!("Invalid {} address: checksum mismatch — likely a typo", coin),
+        other => format!("Invalid {} address: {:.10}... ({})", coin, addr, other),
+    })?;
+    if !decoded_hrp.as_str().eq_ignore_ascii_case(hrp) {
+        return Err(format!("Invalid {} address: '{}1' prefix expected", coin, hrp));
+    }
+    Ok(())
+}
+
 fn validate_btc_address(addr: &str) -> Result<(), String> {
-    if (addr.starts_with("bc1") || addr.starts_with('1') || addr.starts_with('3'))
-        && addr.len() >= 26 && addr.len() <= 90 { return Ok(()); }
-    Err(format!("Invalid BTC address: {:.10}...", addr))
+    if addr.to_lowercase().starts_with("bc1") { return validate_segwit("BTC", addr, "bc"); }
+    if addr.starts_with('1') || addr.starts_with('3') { return validate_base58check("BTC", addr, &[0x00, 0x05]); }
+    Err(format!("Invalid BTC address: {:.10}... (1, 3 or bc1 expected)", addr))
 }
 
 fn validate_eth_address(addr: &str) -> Result<(), String> {
-    if addr.starts_with("0x") && addr.len() == 42
-        && addr[2..].chars().all(|c| c.is_ascii_hexdigit()) { return Ok(()); }
-    Err(format!("Invalid ETH address: {:.10}...", addr))
+    eth_checksum_verified(addr).map(|_| ())
+}
+
+/// EIP-55: a hex letter is uppercase when the matching nibble of keccak-256(lowercase hex) is >= 8
+pub fn to_checksum_address(addr: &str) -> Result<String, String> {
+    use sha3::{Digest, Keccak256};
+    let hex = addr.strip_prefix("0x").or_else(|| addr.strip_prefix("0X")).unwrap_or(addr);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid ETH address: {:.10}... (0x + 40 hex chars expected)", addr));
+    }
+    let lower = hex.to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower.chars().enumerate().map(|(i, c)| {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+    }).collect();
+    Ok(format!("0x{}", checksummed))
+}
+
+/// Ok(true) when a mixed-case address carries a valid EIP-55 checksum, Ok(false) for an
+/// all-lowercase/all-uppercase address (nothing to verify), Err on a checksum mismatch.
+pub fn eth_checksum_verified(addr: &str) -> Result<bool, String> {
+    if !addr.starts_with("0x") {
+        return Err(format!("Invalid ETH address: {:.10}... (0x prefix expected)", addr));
+    }
+    let checksummed = to_checksum_address(addr)?;
+    let hex = &addr[2..];
+    let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if !mixed_case {
+        return Ok(false);
+    }
+    if checksummed != addr {
+        return Err("Invalid ETH address: EIP-55 checksum mismatch — likely a typo".to_string());
+    }
+    Ok(true)
+}
+
+/// Monero base58 alphabet (Bitcoin's: no 0, O, I or l)
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmrAddressKind {
+    /// 4..., 95 chars
+    Primary,
+    /// 8..., 95 chars
+    Subaddress,
+    /// 4..., 106 chars (primary address + 8-byte payment ID)
+    Integrated,
+}
+
+/// Mainnet Monero address: primary (4..., 95), subaddress (8..., 95) or integrated (4..., 106)
+pub fn xmr_address_kind(addr: &str) -> Result<XmrAddressKind, String> {
+    if let Some(c) = addr.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+        return Err(format!("Invalid XMR address: character '{}' is not base58", c));
+    }
+    match (addr.chars().next(), addr.len()) {
+        (Some('4'), 95) => Ok(XmrAddressKind::Primary),
+        (Some('8'), 95) => Ok(XmrAddressKind::Subaddress),
+        (Some('4'), 106) => Ok(XmrAddressKind::Integrated),
+        _ => Err(format!("Invalid XMR address: {:.10}... (4/8 prefix, 95 or 106 chars expected)", addr)),
+    }
+}
+
+pub fn validate_xmr_address(addr: &str) -> Result<(), String> {
+    xmr_address_kind(addr).map(|_| ())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivxAddressKind {
+    /// D..., 34 chars: regular address, also the owner side of a cold-staking delegation
+    Transparent,
+    /// S..., 34 chars: staker address a cold-staking delegation is handed to
+    ColdStaker,
+    /// ps1... (Sapling bech32, 78 chars): shield address, not readable from public data
+    Shield,
+}
+
+/// PIVX mainnet address: transparent/owner (D...), cold staker (S...) or shield (ps1...)
+pub fn pivx_address_kind(addr: &str) -> Result<PivxAddressKind, String> {
+    if let Some(data) = addr.strip_prefix("ps1") {
+        const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+        if addr.len() == 78 && data.chars().all(|c| BECH32_CHARSET.contains(c)) {
+            return Ok(PivxAddressKind::Shield);
+        }
+        return Err(format!("Invalid PIVX shield address: {:.10}... (ps1, 78 bech32 chars expected)", addr));
+    }
+    if let Some(c) = addr.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+        return Err(format!("Invalid PIVX address: character '{}' is not base58", c));
+    }
+    match (addr.chars().next(), addr.len()) {
+        (Some('D'), 34) => Ok(PivxAddressKind::Transparent),
+        (Some('S'), 34) => Ok(PivxAddressKind::ColdStaker),
+        _ => Err(format!("Invalid PIVX address: {:.10}... (D/S prefix, 34 chars expected)", addr)),
+    }
 }
 
-fn validate_xmr_address(addr: &str) -> Result<(), String> {
-    if (addr.starts_with('4') || addr.starts_with('8'))
-        && (addr.len() == 95 || addr.len() == 106) { return Ok(()); }
-    Err(format!("Invalid XMR address: {:.10}...", addr))
+pub fn validate_pivx_address(addr: &str) -> Result<(), String> {
+    pivx_address_kind(addr).map(|_| ())
 }
 
 fn validate_bch_address(addr: &str) -> Result<(), String> {
-    if (addr.starts_with("bitcoincash:") || addr.starts_with('1') || addr.starts_with('3')
-        || addr.starts_with('q') || addr.starts_with('p'))
-        && addr.len() >= 25 && addr.len() <= 120 { return Ok(()); }
-    Err(format!("Invalid BCH address: {:.10}...", addr))
+    normalize_bch_address(addr).map(|_| ())
+}
+
+/// CashAddr BCH-polymod over 5-bit values (checksum valid when the result is 0)
+fn cashaddr_polymod(values: impl Iterator<Item = u8>) -> u64 {
+    const GENERATORS: [u64; 5] = [0x98f2bc8e61, 0x79b76d99e2, 0xf33e5fb3c4, 0xae2eabe2a8, 0x1e4f43e470];
+    let mut c: u64 = 1;
+    for d in values {
+        let c0 = c >> 35;
+        c = ((c & 0x07_ffff_ffff) << 5) ^ d as u64;
+        for (i, g) in GENERATORS.iter().enumerate() {
+            if (c0 >> i) & 1 == 1 { c ^= g; }
+        }
+    }
+    c ^ 1
+}
+
+/// Validated BCH address in the form the explorers expect: CashAddr with its
+/// `bitcoincash:` prefix (added when missing, lowercased), legacy 1.../3... unchanged.
+pub fn normalize_bch_address(addr: &str) -> Result<String, String> {
+    if addr.starts_with('1') || addr.starts_with('3') {
+        validate_base58check("BCH", addr, &[0x00, 0x05])?;
+        return Ok(addr.to_string());
+    }
+    let payload = match addr.split_once(':') {
+        Some((prefix, payload)) if prefix.eq_ignore_ascii_case("bitcoincash") => payload,
+        Some((prefix, _)) => return Err(format!("Invalid BCH address: '{}:' prefix (bitcoincash: expected)", prefix)),
+        None => addr,
+    };
+    if payload != payload.to_lowercase() && payload != payload.to_uppercase() {
+        return Err("Invalid BCH address: mixed case".to_string());
+    }
+    let payload = payload.to_lowercase();
+    const CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    let values: Vec<u8> = payload.chars()
+        .map(|c| CHARSET.find(c).map(|v| v as u8))
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("Invalid BCH address: {:.10}... is not CashAddr", addr))?;
+    // Version byte (type P2PKH/P2SH, 160-bit hash) + 20 bytes + 8 checksum characters
+    if values.len() != 42 || !matches!(values[0], 0 | 1) {
+        return Err(format!("Invalid BCH address: {:.10}... (q/p + 42 chars expected)", addr));
+    }
+    let prefix = "bitcoincash".bytes().map(|b| b & 0x1f).chain(std::iter::once(0));
+    if cashaddr_polymod(prefix.chain(values.iter().copied())) != 0 {
+        return Err("Invalid BCH address: checksum mismatch — likely a typo".to_string());
+    }
+    Ok(format!("bitcoincash:{}", payload))
 }
 
 fn validate_ltc_address(addr: &str) -> Result<(), String> {
-    if (addr.starts_with('L') || addr.starts_with('M') || addr.starts_with('3') || addr.starts_with("ltc1"))
-        && addr.len() >= 26 && addr.len() <= 90 { return Ok(()); }
-    Err(format!("Invalid LTC address: {:.10}...", addr))
+    if addr.to_lowercase().starts_with("ltc1") { return validate_segwit("LTC", addr, "ltc"); }
+    // L (P2PKH 0x30), M (P2SH 0x32) and the legacy 3 (P2SH 0x05)
+    if addr.starts_with('L') || addr.starts_with('M') || addr.starts_with('3') {
+        return validate_base58check("LTC", addr, &[0x30, 0x32, 0x05]);
+    }
+    Err(format!("Invalid LTC address: {:.10}... (L, M, 3 or ltc1 expected)", addr))
+}
+
+fn validate_doge_address(addr: &str) -> Result<(), String> {
+    // D (P2PKH 0x1e), 9/A (P2SH 0x16)
+    if addr.starts_with('D') || addr.starts_with('9') || addr.starts_with('A') {
+        return validate_base58check("DOGE", addr, &[0x1e, 0x16]);
+    }
+    Err(format!("Invalid DOGE address: {:.10}... (D, 9 or A expected)", addr))
 }
 
+/// SS58 network prefix of Polkadot
+const POLKADOT_SS58_PREFIX: u8 = 0;
+
+/// SS58: base58(prefix byte + 32-byte public key + first 2 bytes of blake2b-512("SS58PRE" || prefix || key))
 fn validate_dot_address(addr: &str) -> Result<(), String> {
-    if addr.starts_with('1') && addr.len() >= 46 && addr.len() <= 50 { return Ok(()); }
-    Err(format!("Invalid DOT address: {:.10}...", addr))
+    use blake2::{Blake2b512, Digest};
+    let data = bs58::decode(addr).into_vec()
+        .map_err(|_| format!("Invalid DOT address: {:.10}... is not base58", addr))?;
+    if data.len() != 35 {
+        return Err(format!("Invalid DOT address: {:.10}... (32-byte account expected)", addr));
+    }
+    let (body, checksum) = data.split_at(33);
+    let hash = Blake2b512::new().chain_update(b"SS58PRE").chain_update(body).finalize();
+    if hash[..2] != *checksum {
+        return Err("Invalid DOT address: checksum mismatch — likely a typo".to_string());
+    }
+    if body[0] != POLKADOT_SS58_PREFIX {
+        return Err(format!("Invalid DOT address: SS58 network prefix {} is not Polkadot (0)", body[0]));
+    }
+    Ok(())
+}
+
+/// Solana: base58 of a 32-byte ed25519 public key, no checksum
+fn validate_sol_address(addr: &str) -> Result<(), String> {
+    let key = bs58::decode(addr).into_vec()
+        .map_err(|_| format!("Invalid SOL address: {:.10}... is not base58", addr))?;
+    if key.len() != 32 {
+        return Err(format!("Invalid SOL address: {:.10}... ({} bytes, 32-byte public key expected)", addr, key.len()));
+    }
+    Ok(())
+}
+
+/// Plain bech32 (not segwit): returns the lowercase HRP and the decoded bytes
+fn decode_bech32(coin: &str, addr: &str) -> Result<(String, Vec<u8>), String> {
+    let (hrp, data) = bech32::decode(addr).map_err(|e| match e {
+        bech32::DecodeError::Checksum(_) => format!("Invalid {} address: checksum mismatch — likely a typo", coin),
+        other => format!("Invalid {} address: {:.10}... ({})", coin, addr, other),
+    })?;
+    Ok((hrp.to_lowercase(), data))
+}
+
+/// Cardano Shelley (CIP-19): bech32 'addr1' payment or 'stake1' reward address on mainnet
+fn validate_ada_address(addr: &str) -> Result<(), String> {
+    let lower = addr.to_lowercase();
+    if !lower.starts_with("addr1") && !lower.starts_with("stake1") {
+        return Err(format!("Invalid ADA address: {:.10}... (addr1 or stake1 expected)", addr));
+    }
+    let (hrp, data) = decode_bech32("ADA", addr)?;
+    let header = *data.first().ok_or_else(|| "Invalid ADA address: empty payload".to_string())?;
+    let kind = header >> 4;
+    let kind_ok = match hrp.as_str() {
+        "addr" => kind <= 7,
+        "stake" => kind == 14 || kind == 15,
+        _ => false,
+    };
+    if !kind_ok {
+        return Err(format!("Invalid ADA address: header type {} does not match '{}1'", kind, hrp));
+    }
+    if header & 0x0f != 1 {
+        return Err("Invalid ADA address: not a mainnet address".to_string());
+    }
+    Ok(())
+}
+
+/// XRP Ledger classic address: Base58Check with the ripple alphabet, version 0x00 + 20-byte account ID
+fn validate_xrp_address(addr: &str) -> Result<(), String> {
+    if !addr.starts_with('r') {
+        return Err(format!("Invalid XRP address: {:.10}... (r expected)", addr));
+    }
+    let payload = bs58::decode(addr).with_alphabet(bs58::Alphabet::RIPPLE).with_check(None).into_vec()
+        .map_err(|e| match e {
+            bs58::decode::Error::InvalidChecksum { .. } => "Invalid XRP address: checksum mismatch — likely a typo".to_string(),
+            _ => format!("Invalid XRP address: {:.10}... is not base58", addr),
+        })?;
+    if payload.len() != 21 || payload[0] != 0x00 {
+        return Err(format!("Invalid XRP address: {:.10}... (wrong version byte or length)", addr));
+    }
+    Ok(())
+}
+
+/// NEAR: implicit account (64 lowercase hex) or named '.near' account
+fn validate_near_address(addr: &str) -> Result<(), String> {
+    if addr.len() == 64 && addr.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)) {
+        return Ok(());
+    }
+    if addr.len() < 2 || addr.len() > 64 {
+        return Err("Invalid NEAR account: 2 to 64 characters expected".to_string());
+    }
+    // Each dot-separated part: [a-z0-9] runs joined by single '-' or '_'
+    let part_ok = |part: &str| {
+        !part.is_empty()
+            && part.split(['-', '_']).all(|run| !run.is_empty() && run.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()))
+    };
+    if !addr.split('.').all(part_ok) {
+        return Err(format!("Invalid NEAR account: {:.10}... (lowercase letters, digits and single '-', '_' or '.' separators only)", addr));
+    }
+    if !addr.ends_with(".near") {
+        return Err(format!("Invalid NEAR account: {:.10}... (.near account or 64-hex implicit account expected)", addr));
+    }
+    Ok(())
+}
+
+/// Cosmos Hub: bech32 'cosmos1' + 20-byte (account) or 32-byte (module/ICA) address
+fn validate_atom_address(addr: &str) -> Result<(), String> {
+    if !addr.to_lowercase().starts_with("cosmos1") {
+        return Err(format!("Invalid ATOM address: {:.10}... (cosmos1 expected)", addr));
+    }
+    let (_, data) = decode_bech32("ATOM", addr)?;
+    if data.len() != 20 && data.len() != 32 {
+        return Err(format!("Invalid ATOM address: {} bytes (20 or 32 expected)", data.len()));
+    }
+    Ok(())
 }
 
 pub fn validate_balance(balance: Option<f64>) -> Result<(), String> {
@@ -108,3 +401,308 @@ pub fn validate_setting_key(key: &str) -> Result<(), String> {
 pub fn validate_setting_value(value: &str) -> Result<(), String> {
     validate_string("Setting value", value, MAX_SETTING_VALUE_LEN)
 }
+
+const MAX_URL_LEN: usize = 500;
+
+/// http(s) node URL; `.onion` hosts must be Tor v3 addresses (56 base32 characters)
+pub fn validate_node_url(url: &str) -> Result<(), String> {
+    validate_non_empty("Node URL", url, MAX_URL_LEN)?;
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid node URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Invalid node URL scheme: '{}' (http or https expected)", parsed.scheme()));
+    }
+    let host = parsed.host_str().ok_or("Node URL has no host")?;
+    // Basic auth as user:password@host; a password alone can't be sent
+    if parsed.username().is_empty() && parsed.password().is_some() {
+        return Err("Node URL credentials need a user name (user:password@host)".to_string());
+    }
+    if let Some(service) = host.strip_suffix(".onion") {
+        let label = service.rsplit('.').next().unwrap_or(service);
+        if label.len() != 56 || !label.chars().all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c)) {
+            return Err("Invalid .onion address (Tor v3, 56 characters expected)".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// SOCKS5 (socks5:// or socks5h://) or HTTP(S) proxy URL with a host and a port
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    validate_non_empty("Proxy URL", url, MAX_URL_LEN)?;
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    if !matches!(parsed.scheme(), "socks5" | "socks5h" | "http" | "https") {
+        return Err("Proxy must be socks5://, socks5h://, http:// or https://".to_string());
+    }
+    if parsed.host_str().is_none() || parsed.port().is_none() {
+        return Err("Proxy URL needs a host and a port (e.g. socks5h://127.0.0.1:9050)".to_string());
+    }
+    Ok(())
+}
+
+/// Address pasted as a payment URI (BIP-21, EIP-681, Monero), split into its parts
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PaymentUri {
+    pub address: String,
+    /// Asset of the scheme; None when the input was not a known URI
+    pub asset: Option<String>,
+    pub amount: Option<f64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+}
+
+const PAYMENT_SCHEMES: &[(&str, &str)] = &[
+    ("bitcoin", "btc"),
+    ("litecoin", "ltc"),
+    ("bitcoincash", "bch"),
+    ("ethereum", "eth"),
+    ("monero", "xmr"),
+    ("dogecoin", "doge"),
+];
+
+/// %XX sequences decoded as UTF-8 (invalid bytes replaced); '+' is left alone as in RFC 3986
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_amount(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|a| a.is_finite() && *a >= 0.0)
+}
+
+/// Extracts the bare address of `bitcoin:`, `litecoin:`, `bitcoincash:`, `ethereum:`, `monero:`
+/// and `dogecoin:` URIs, plus amount and label. Anything else comes back unchanged as the address.
+pub fn parse_payment_uri(input: &str) -> PaymentUri {
+    let input = input.trim();
+    let unchanged = || PaymentUri { address: input.to_string(), asset: None, amount: None, label: None, message: None };
+    let Some((scheme, rest)) = input.split_once(':') else { return unchanged() };
+    let scheme = scheme.to_ascii_lowercase();
+    let Some(&(_, asset)) = PAYMENT_SCHEMES.iter().find(|(s, _)| *s == scheme) else { return unchanged() };
+
+    let (target, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let params: Vec<(String, String)> = query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_ascii_lowercase(), percent_decode(v)))
+        .collect();
+    let param = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()).filter(|v| !v.is_empty());
+
+    let (address, amount, label, message) = match asset {
+        "eth" => {
+            // ethereum:[pay-]<address>[@chain_id][/function]?params
+            let target = target.strip_prefix("pay-").unwrap_or(target);
+            let (target, function) = target.split_once('/').unwrap_or((target, ""));
+            let contract = target.split('@').next().unwrap_or(target);
+            if function == "transfer" {
+                // ERC-20 transfer: the recipient is a parameter, the amount is in token units of unknown decimals
+                (param("address").unwrap_or_else(|| contract.to_string()), None, None, None)
+            } else {
+                let amount = param("value").and_then(|v| parse_amount(&v)).map(|wei| wei / 1e18);
+                (contract.to_string(), amount, None, None)
+            }
+        }
+        "xmr" => (
+            target.to_string(),
+            param("tx_amount").and_then(|v| parse_amount(&v)),
+            param("recipient_name"),
+            param("tx_description"),
+        ),
+        _ => (target.to_string(), param("amount").and_then(|v| parse_amount(&v)), param("label"), param("message")),
+    };
+
+    // QR codes carry bech32 in uppercase; the validators expect lowercase
+    let bech32_prefixes: &[&str] = match asset {
+        "btc" => &["BC1", "TB1"],
+        "ltc" => &["LTC1"],
+        "bch" => &["Q", "P"],
+        _ => &[],
+    };
+    let address = if bech32_prefixes.iter().any(|p| address.starts_with(p)) && !address.chars().any(|c| c.is_ascii_lowercase()) {
+        address.to_ascii_lowercase()
+    } else {
+        address
+    };
+    PaymentUri { address, asset: Some(asset.to_string()), amount, label, message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Changes one character in the middle, keeping the address alphabet and case
+    fn typo(addr: &str) -> String {
+        let mut chars: Vec<char> = addr.chars().collect();
+        let i = chars.len() / 2;
+        let replacement = if chars[i].eq_ignore_ascii_case(&'q') { 'p' } else { 'q' };
+        chars[i] = if addr == addr.to_ascii_uppercase() { replacement.to_ascii_uppercase() } else { replacement };
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn checksummed_addresses() {
+        let valid = [
+            ("BTC", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+            ("BTC", "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"),
+            ("BTC", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            ("BTC", "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4"),
+            ("BTC", "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"),
+            ("LTC", "LVg2kJoFNg45Nbpy53h7Fe1wKyeXVRhMH9"),
+            ("LTC", "MQMcJhpWHYVeQArcZR3sBgyPZxxRtnH441"),
+            ("LTC", "ltc1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5dyg36p"),
+            ("DOGE", "DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L"),
+            ("DOGE", "9rXbkMyi1S6thykRoXAZcY8fwUKYsy6cXE"),
+            ("BCH", "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a"),
+            ("BCH", "ppm2qsznhks23z7629mms6s4cwef74vcwvn0h829pq"),
+            ("BCH", "BITCOINCASH:QPM2QSZNHKS23Z7629MMS6S4CWEF74VCWVY22GDX6A"),
+            ("BCH", "1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu"),
+            ("DOT", "13UVJyLnbVp9RBZYFwFGyDvVd1y27Tt8tkntv6Q7JVPhFsTB"),
+            ("DOT", "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"),
+            // CIP-19 test vectors
+            ("ADA", "addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgse35a3x"),
+            ("ADA", "addr1vx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzers66hrl8"),
+            ("ADA", "stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw"),
+            ("XRP", "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh"),
+            ("XRP", "rrrrrrrrrrrrrrrrrrrrrhoLvTp"),
+            ("ATOM", "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"),
+        ];
+        for (asset, addr) in valid {
+            assert_eq!(validate_address(asset, addr), Ok(()), "{} {}", asset, addr);
+            let err = validate_address(asset, &typo(addr)).unwrap_err();
+            assert!(err.contains("checksum mismatch"), "{} {}: {}", asset, typo(addr), err);
+        }
+    }
+
+    #[test]
+    fn eip55_checksums() {
+        // EIP-55 test vectors
+        for addr in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(eth_checksum_verified(addr), Ok(true), "{}", addr);
+            assert_eq!(to_checksum_address(&addr.to_lowercase()).unwrap(), addr);
+            assert_eq!(eth_checksum_verified(&addr.to_lowercase()), Ok(false));
+            // One letter with the wrong case
+            let flipped = addr.replacen('a', "A", 1);
+            assert!(validate_address("USDC", &flipped).unwrap_err().contains("checksum mismatch"), "{}", flipped);
+        }
+        // All-caps: accepted, but nothing was verified
+        assert_eq!(eth_checksum_verified("0x52908400098527886E0F7030069857D2E4169EE7"), Ok(false));
+        assert!(validate_address("ETC", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe").is_err());
+    }
+
+    #[test]
+    fn bch_normalized_form() {
+        let cashaddr = "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a";
+        assert_eq!(normalize_bch_address(&cashaddr[12..]).unwrap(), cashaddr);
+        assert_eq!(normalize_bch_address(&cashaddr.to_uppercase()).unwrap(), cashaddr);
+        assert_eq!(normalize_bch_address("1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu").unwrap(), "1BpEi6DfDAUFd7GtittLSdBeYJvcoaVggu");
+    }
+
+    #[test]
+    fn unchecksummed_addresses() {
+        for (asset, addr) in [
+            ("SOL", "So11111111111111111111111111111111111111112"),
+            ("SOL", "11111111111111111111111111111111"),
+            ("NEAR", "alice.near"),
+            ("NEAR", "app_1.some-dao.near"),
+            ("NEAR", "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de"),
+        ] {
+            assert_eq!(validate_address(asset, addr), Ok(()), "{} {}", asset, addr);
+        }
+    }
+
+    #[test]
+    fn wrong_format_is_not_a_checksum_error() {
+        let invalid = [
+            ("BTC", "LVg2kJoFNg45Nbpy53h7Fe1wKyeXVRhMH9"),
+            ("BTC", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfN0"),
+            ("BTC", "ltc1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5dyg36p"),
+            ("BTC", "bc1qw508d6qejxtdg4y5r3zarvary0c5XW7KV8F3T4"),
+            ("LTC", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+            ("LTC", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            ("DOGE", "LVg2kJoFNg45Nbpy53h7Fe1wKyeXVRhMH9"),
+            ("DOGE", "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"),
+            ("BCH", "bchtest:qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a"),
+            ("BCH", "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwvY22gdx6a"),
+            ("BCH", "bitcoincash:qpm2qsznhks23z7629mms6s4cwef74vcwv"),
+            // Generic Substrate (prefix 42) account
+            ("DOT", "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            ("DOT", "13UVJyLnbVp9RBZYFwFGyDvVd1y27Tt8tkntv6Q7JVPh"),
+            ("SOL", "So1111111111111111111111111111111111111111"),
+            ("SOL", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            ("ADA", "DdzFFzCqrhsw3prhfMFDNFowbzUku3QmrMwarfjUbWXRisodn97R436SHHkEd5k3"),
+            ("ADA", "addr_test1vz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzerspjrlsz"),
+            ("XRP", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"),
+            ("XRP", "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyT0"),
+            ("NEAR", "Alice.near"),
+            ("NEAR", "alice..near"),
+            ("NEAR", "alice-.near"),
+            ("NEAR", "alice.testnet"),
+            ("NEAR", "98793CD91A3F870FB126F66285808C7E094AFCFC4EDA8A970F6648CDF0DBD6DE"),
+            ("ATOM", "osmo19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"),
+            ("ATOM", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+        ];
+        for (asset, addr) in invalid {
+            let err = validate_address(asset, addr).unwrap_err();
+            assert!(!err.contains("checksum"), "{} {}: {}", asset, addr, err);
+        }
+    }
+
+    #[test]
+    fn payment_uris_yield_valid_addresses() {
+        let uri = parse_payment_uri("bitcoin:bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4?amount=0.1&label=Caf%C3%A9%20du%20coin&message=Facture%20%2342");
+        assert_eq!(uri.address, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert_eq!(uri.asset.as_deref(), Some("btc"));
+        assert_eq!(uri.amount, Some(0.1));
+        assert_eq!(uri.label.as_deref(), Some("Café du coin"));
+        assert_eq!(uri.message.as_deref(), Some("Facture #42"));
+        assert!(validate_address("BTC", &uri.address).is_ok());
+
+        // QR codes: whole URI in uppercase
+        let uri = parse_payment_uri("BITCOIN:BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4?AMOUNT=1");
+        assert_eq!(uri.address, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        assert_eq!(uri.amount, Some(1.0));
+        let uri = parse_payment_uri("bitcoincash:QPM2QSZNHKS23Z7629MMS6S4CWEF74VCWVY22GDX6A");
+        assert_eq!(uri.address, "qpm2qsznhks23z7629mms6s4cwef74vcwvy22gdx6a");
+        assert!(validate_address("BCH", &uri.address).is_ok());
+        // Base58 is case-sensitive and left alone
+        let uri = parse_payment_uri("litecoin:LVg2kJoFNg45Nbpy53h7Fe1wKyeXVRhMH9?label=Cold");
+        assert_eq!((uri.address.as_str(), uri.label.as_deref()), ("LVg2kJoFNg45Nbpy53h7Fe1wKyeXVRhMH9", Some("Cold")));
+        assert!(validate_address("LTC", &uri.address).is_ok());
+        let uri = parse_payment_uri("dogecoin:DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L?amount=abc");
+        assert_eq!((uri.asset.as_deref(), uri.amount), (Some("doge"), None));
+        assert!(validate_address("DOGE", &uri.address).is_ok());
+
+        let uri = parse_payment_uri("ethereum:pay-0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed@1?value=2.014e18");
+        assert_eq!(uri.address, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert!((uri.amount.unwrap() - 2.014).abs() < 1e-12);
+        assert!(validate_address("ETH", &uri.address).is_ok());
+        let uri = parse_payment_uri("ethereum:0xdAC17F958D2ee523a2206206994597C13D831ec7/transfer?address=0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed&uint256=1000000");
+        assert_eq!((uri.address.as_str(), uri.amount), ("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", None));
+
+        let uri = parse_payment_uri("monero:44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A?tx_amount=0.5&recipient_name=Alice&tx_description=Loyer%20mars");
+        assert_eq!((uri.asset.as_deref(), uri.amount), (Some("xmr"), Some(0.5)));
+        assert_eq!((uri.label.as_deref(), uri.message.as_deref()), (Some("Alice"), Some("Loyer mars")));
+
+        // Not a payment URI: unchanged
+        for input in ["bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", "solana:So11111111111111111111111111111111111111112", "addr1:xyz"] {
+            assert_eq!(parse_payment_uri(input), PaymentUri { address: input.to_string(), asset: None, amount: None, label: None, message: None });
+        }
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
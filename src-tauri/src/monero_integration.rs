@@ -1,7 +1,28 @@
 // monero_integration.rs — Monero RPC integration for Janus Monitor
-// Supports: monero daemon RPC (get_info) and monero-wallet-rpc (get_balance, get_transfers)
+// Supports: monero daemon RPC (get_info) and monero-wallet-rpc (generate_from_keys,
+// open_wallet, refresh, get_balance, get_transfers)
+//
+// The balance is scanned by monero-wallet-rpc from the wallet's own keys: a
+// watch-only wallet (address + view key, plus the spend key when the user supplied
+// one) is created once on the rpc daemon, then reopened and refreshed on each call.
 
 use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::hash::sha256;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{http, input_validation, log_address, LAST_PRICES, log_balance, monero_proxy, read_setting, secure_log, DbState};
+
+/// 1 XMR = 10^12 piconero
+const PICONERO_PER_XMR: f64 = 1_000_000_000_000.0;
+
+//
+// STRUCTURES DE DONNÉES MONERO
+//
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoneroNodeInfo {
@@ -9,8 +30,180 @@ pub struct MoneroNodeInfo {
     pub height: u64,
     pub success: bool,
     pub error: Option<String>,
+    /// The request went through the proxy_url setting
+    #[serde(default)]
+    pub via_proxy: bool,
+}
+
+/// Balance scannée par monero-wallet-rpc
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoneroBalanceResult {
+    pub balance: f64,
+    pub unlocked_balance: f64,
+    /// Hauteur jusqu'à laquelle le wallet a été scanné
+    pub last_scanned_height: u64,
+    /// Hauteur du démon (égale à la hauteur scannée quand aucun démon n'est fourni)
+    pub network_height: u64,
+    pub transactions: Vec<MoneroTransaction>,
+    /// Sous-adresse dont la balance est rapportée (None = compte 0 entier)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subaddress_index: Option<MoneroSubaddressIndex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subaddress: Option<String>,
+}
+
+/// Transaction Monero (clés JSON historiques: hash, amount, direction, height, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoneroTransaction {
+    pub hash: String,
+    #[serde(rename = "amount")]
+    pub amount_xmr: f64,
+    /// "in", "out", "pending" (sortante non confirmée) ou "pool" (entrante non confirmée)
+    pub direction: String,
+    pub height: u64,
+    pub timestamp: i64,
+    pub confirmations: u64,
+    /// Bloc ou timestamp avant lequel les fonds restent verrouillés (0 = standard)
+    pub unlock_time: u64,
+    pub fee: f64,
+    pub unlocked: bool,
+    /// Valeur au dernier prix connu (get_prices)
+    pub amount_eur: Option<f64>,
+    pub amount_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subaddress_index: Option<MoneroSubaddressIndex>,
+}
+
+impl MoneroTransaction {
+    pub fn is_incoming(&self) -> bool {
+        self.direction == "in" || self.direction == "pool"
+    }
+}
+
+/// (compte, index) d'une sous-adresse, `subaddr_index` de wallet-rpc
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MoneroSubaddressIndex {
+    #[serde(alias = "account")]
+    pub major: u32,
+    #[serde(alias = "index")]
+    pub minor: u32,
+}
+
+/// Données de wallet Monero pour les appels backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoneroWalletData {
+    pub address: String,
+    pub view_key: String,
+    pub spend_key: Option<String>,
+    /// URL du monero-wallet-rpc
+    pub node: String,
+    /// Hauteur de départ du scan à la création du wallet (0 = depuis la genèse)
+    pub restore_height: u64,
+    /// Limiter balance et transferts à une sous-adresse plutôt qu'au compte 0 entier
+    #[serde(default)]
+    pub subaddress_index: Option<MoneroSubaddressIndex>,
+}
+
+/// Erreur Monero personnalisée
+#[derive(Debug, thiserror::Error)]
+pub enum MoneroError {
+    #[error("Adresse Monero invalide: {0}")]
+    InvalidAddress(String),
+
+    #[error("View key invalide: {0}")]
+    InvalidViewKey(String),
+
+    #[error("Spend key invalide: {0}")]
+    InvalidSpendKey(String),
+
+    #[error("Échec de la connexion au nœud Monero: {0}")]
+    NodeConnectionFailed(String),
+
+    #[error("Échec de l'appel RPC: {0}")]
+    RpcCallFailed(String),
+}
+
+impl Serialize for MoneroError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+//
+// VALIDATION MONERO
+//
+
+/// Valider une adresse Monero (principale, sous-adresse ou intégrée)
+pub fn validate_monero_address(address: &str) -> Result<(), MoneroError> {
+    input_validation::validate_xmr_address(address).map_err(MoneroError::InvalidAddress)
+}
+
+/// Valider une view key Monero (64 caractères hexadécimaux)
+pub fn validate_view_key(view_key: &str) -> Result<(), MoneroError> {
+    if view_key.len() != 64 {
+        return Err(MoneroError::InvalidViewKey(format!(
+            "Longueur incorrecte: {} (attendu: 64)", view_key.len()
+        )));
+    }
+
+    if !view_key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(MoneroError::InvalidViewKey(
+            "La view key doit être en hexadécimal".to_string()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Valider une spend key Monero (64 caractères hexadécimaux, optionnelle)
+pub fn validate_spend_key(spend_key: &Option<String>) -> Result<(), MoneroError> {
+    if let Some(key) = spend_key {
+        if key.len() != 64 {
+            return Err(MoneroError::InvalidSpendKey(format!(
+                "Longueur incorrecte: {} (attendu: 64)", key.len()
+            )));
+        }
+
+        if !key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(MoneroError::InvalidSpendKey(
+                "La spend key doit être en hexadécimal".to_string()
+            ));
+        }
+    }
+
+    Ok(())
 }
 
+/// Valider les données complètes du wallet Monero
+pub fn validate_monero_wallet_data(data: &MoneroWalletData) -> Result<(), MoneroError> {
+    // generate_from_keys reconstruit le wallet depuis l'adresse principale
+    match input_validation::xmr_address_kind(&data.address).map_err(MoneroError::InvalidAddress)? {
+        input_validation::XmrAddressKind::Primary => {}
+        kind => return Err(MoneroError::InvalidAddress(format!(
+            "{:?}: utilisez l'adresse principale du wallet (4...) et subaddress_index", kind
+        ))),
+    }
+    validate_view_key(&data.view_key)?;
+    validate_spend_key(&data.spend_key)?;
+
+    if data.node.is_empty() {
+        return Err(MoneroError::NodeConnectionFailed(
+            "URL du nœud vide".to_string()
+        ));
+    }
+
+    Ok(())
+}
+
+//
+// CLIENT JSON-RPC
+//
+
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonRpcRequest {
     jsonrpc: String,
@@ -20,180 +213,1026 @@ struct JsonRpcRequest {
     params: Option<serde_json::Value>,
 }
 
-#[tauri::command]
-pub async fn test_monero_node(node_url: String) -> Result<MoneroNodeInfo, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    // Try daemon RPC get_info first
-    let rpc_request = JsonRpcRequest {
-        jsonrpc: "2.0".to_string(),
-        id: "0".to_string(),
-        method: "get_info".to_string(),
-        params: None,
-    };
+/// Options de connexion au wallet-rpc / démon: `--rpc-login user:pass` (HTTP digest)
+/// et certificats auto-signés.
+#[derive(Debug, Clone, Default)]
+pub struct MoneroRpcOptions {
+    pub rpc_user: Option<String>,
+    pub rpc_password: Option<String>,
+    pub allow_self_signed: bool,
+}
 
-    match client.post(format!("{}/json_rpc", node_url))
-        .json(&rpc_request)
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                if let Ok(data) = response.json::<serde_json::Value>().await {
-                    if let Some(result) = data.get("result") {
-                        let height = result.get("height")
-                            .and_then(|h| h.as_u64())
-                            .unwrap_or(0);
-                        return Ok(MoneroNodeInfo {
-                            url: node_url,
-                            height,
-                            success: true,
-                            error: None,
-                        });
+impl MoneroRpcOptions {
+    pub fn new(rpc_user: Option<String>, rpc_password: Option<String>, allow_self_signed: Option<bool>) -> Self {
+        Self {
+            rpc_user: rpc_user.filter(|u| !u.is_empty()),
+            rpc_password,
+            allow_self_signed: allow_self_signed.unwrap_or(false),
+        }
+    }
+
+    fn credentials(&self) -> Option<(&str, &str)> {
+        self.rpc_user.as_deref().map(|u| (u, self.rpc_password.as_deref().unwrap_or("")))
+    }
+}
+
+/// Challenge `WWW-Authenticate: Digest ...` reçu du serveur, réutilisé tant qu'il reste valide
+#[derive(Debug, Clone, PartialEq)]
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    qop_auth: bool,
+    sess: bool,
+    nonce_count: u32,
+}
+
+/// Parse un en-tête `Digest realm="..", nonce="..", qop="auth", algorithm=MD5`.
+/// None pour les autres schémas ou les algorithmes non MD5.
+fn parse_digest_challenge(header: &str) -> Option<DigestChallenge> {
+    let rest = header.trim().strip_prefix("Digest")?.trim_start();
+    let mut fields = std::collections::HashMap::new();
+    let mut chars = rest.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=' && *c != ',')).collect();
+        if name.is_empty() {
+            break;
+        }
+        let value = if chars.next_if_eq(&'=').is_some() {
+            if chars.next_if_eq(&'"').is_some() {
+                let mut v = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => v.extend(chars.next()),
+                        '"' => break,
+                        c => v.push(c),
                     }
                 }
+                v
+            } else {
+                std::iter::from_fn(|| chars.next_if(|c| *c != ',')).collect::<String>().trim().to_string()
             }
-            Ok(MoneroNodeInfo {
-                url: node_url,
-                height: 0,
-                success: false,
-                error: Some("Réponse invalide du nœud".to_string()),
-            })
+        } else {
+            String::new()
+        };
+        fields.insert(name.trim().to_ascii_lowercase(), value);
+    }
+
+    let sess = match fields.get("algorithm").map(|a| a.to_ascii_uppercase()) {
+        None => false,
+        Some(a) if a == "MD5" => false,
+        Some(a) if a == "MD5-SESS" => true,
+        Some(_) => return None,
+    };
+    Some(DigestChallenge {
+        realm: fields.get("realm").cloned().unwrap_or_default(),
+        nonce: fields.get("nonce").cloned()?,
+        opaque: fields.get("opaque").cloned(),
+        qop_auth: fields.get("qop").is_some_and(|q| q.split(',').any(|v| v.trim() == "auth")),
+        sess,
+        nonce_count: 0,
+    })
+}
+
+fn md5_hex(data: &str) -> String {
+    use md5::{Digest, Md5};
+    hex::encode(Md5::digest(data.as_bytes()))
+}
+
+/// Valeur de l'en-tête Authorization (RFC 7616, MD5 / MD5-sess, qop=auth)
+fn digest_authorization(challenge: &DigestChallenge, user: &str, password: &str, method: &str, uri: &str, cnonce: &str) -> String {
+    let mut ha1 = md5_hex(&format!("{}:{}:{}", user, challenge.realm, password));
+    if challenge.sess {
+        ha1 = md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, cnonce));
+    }
+    let ha2 = md5_hex(&format!("{}:{}", method, uri));
+    let nc = format!("{:08x}", challenge.nonce_count);
+    let response = if challenge.qop_auth {
+        md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, challenge.nonce, nc, cnonce, ha2))
+    } else {
+        md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, ha2))
+    };
+
+    let mut header = format!(
+        "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+        user, challenge.realm, challenge.nonce, uri, if challenge.sess { "MD5-sess" } else { "MD5" }, response
+    );
+    if challenge.qop_auth {
+        header.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\"", nc, cnonce));
+    }
+    if let Some(opaque) = &challenge.opaque {
+        header.push_str(&format!(", opaque=\"{}\"", opaque));
+    }
+    header
+}
+
+struct MoneroRpcClient {
+    client: reqwest::Client,
+    url: String,
+    /// Per-request: scans run far longer than the shared client's default
+    timeout: std::time::Duration,
+    via_proxy: bool,
+    options: MoneroRpcOptions,
+    challenge: std::sync::Mutex<Option<DigestChallenge>>,
+}
+
+impl MoneroRpcClient {
+    fn new(url: &str, timeout_secs: u64, options: &MoneroRpcOptions) -> Result<Self, MoneroError> {
+        // Nœuds Monero: toujours via le proxy s'il est configuré (.onion compris)
+        let proxy = monero_proxy().map_err(MoneroError::NodeConnectionFailed)?;
+        let client = http::client_with(proxy.as_deref(), options.allow_self_signed)
+            .map_err(MoneroError::NodeConnectionFailed)?;
+        Ok(Self {
+            client,
+            timeout: std::time::Duration::from_secs(timeout_secs),
+            url: format!("{}/json_rpc", url.trim_end_matches('/')),
+            via_proxy: proxy.is_some(),
+            options: options.clone(),
+            challenge: std::sync::Mutex::new(None),
+        })
+    }
+
+    /// Authorization pour la prochaine requête à partir du dernier challenge connu
+    fn next_authorization(&self) -> Option<String> {
+        let (user, password) = self.options.credentials()?;
+        let mut guard = self.challenge.lock().ok()?;
+        let challenge = guard.as_mut()?;
+        challenge.nonce_count += 1;
+        let uri = reqwest::Url::parse(&self.url).map(|u| u.path().to_string()).unwrap_or_else(|_| "/json_rpc".to_string());
+        let cnonce = hex::encode(sodiumoxide::randombytes::randombytes(8));
+        Some(digest_authorization(challenge, user, password, "POST", &uri, &cnonce))
+    }
+
+    async fn post(&self, request: &JsonRpcRequest) -> Result<reqwest::Response, MoneroError> {
+        let mut builder = self.client.post(&self.url).timeout(self.timeout).json(request);
+        if let Some(authorization) = self.next_authorization() {
+            builder = builder.header(reqwest::header::AUTHORIZATION, authorization);
         }
-        Err(e) => {
-            Ok(MoneroNodeInfo {
-                url: node_url,
-                height: 0,
-                success: false,
-                error: Some(format!("Nœud inaccessible: {}", e)),
-            })
+        builder.send().await.map_err(|e| MoneroError::NodeConnectionFailed(e.to_string()))
+    }
+
+    /// Appel JSON-RPC, renvoie le champ `result`
+    async fn call(&self, method: &str, params: Option<serde_json::Value>) -> Result<serde_json::Value, MoneroError> {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: "0".to_string(),
+            method: method.to_string(),
+            params,
+        };
+        let mut response = self.post(&request).await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            if self.options.credentials().is_none() {
+                return Err(MoneroError::RpcCallFailed(format!("{}: authentification requise (--rpc-login)", method)));
+            }
+            // Nouveau challenge (premier appel ou nonce expiré): une seule nouvelle tentative
+            let challenge = response.headers()
+                .get_all(reqwest::header::WWW_AUTHENTICATE)
+                .iter()
+                .filter_map(|h| h.to_str().ok())
+                .filter_map(parse_digest_challenge)
+                .min_by_key(|c| c.sess);
+            let Some(challenge) = challenge else {
+                return Err(MoneroError::RpcCallFailed(format!("{}: schéma d'authentification non supporté", method)));
+            };
+            if let Ok(mut guard) = self.challenge.lock() {
+                *guard = Some(challenge);
+            }
+            response = self.post(&request).await?;
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                return Err(MoneroError::RpcCallFailed(format!("{}: identifiants RPC refusés", method)));
+            }
+        }
+        if !response.status().is_success() {
+            return Err(MoneroError::RpcCallFailed(format!("{}: HTTP {}", method, response.status())));
         }
+        let mut data: serde_json::Value = response.json().await
+            .map_err(|e| MoneroError::RpcCallFailed(format!("{}: {}", method, e)))?;
+        if let Some(error) = data.get("error") {
+            let msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Erreur RPC inconnue");
+            return Err(MoneroError::RpcCallFailed(format!("{}: {}", method, msg)));
+        }
+        data.get_mut("result")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| MoneroError::RpcCallFailed(format!("{}: réponse sans résultat", method)))
     }
 }
 
-#[tauri::command]
-pub async fn get_monero_balance(
-    _address: String,
-    _view_key: String,
-    _spend_key: Option<String>,
-    node: String,
-) -> Result<f64, String> {
-    // Monero wallet-rpc get_balance — requires wallet-rpc running with wallet loaded
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(15))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let rpc_request = JsonRpcRequest {
-        jsonrpc: "2.0".to_string(),
-        id: "0".to_string(),
-        method: "get_balance".to_string(),
-        params: Some(serde_json::json!({ "account_index": 0 })),
-    };
+//
+// WALLET WATCH-ONLY (monero-wallet-rpc)
+//
 
-    match client.post(format!("{}/json_rpc", node))
-        .json(&rpc_request)
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                if let Ok(data) = response.json::<serde_json::Value>().await {
-                    if let Some(result) = data.get("result") {
-                        // Balance is in atomic units (piconero = 1e-12 XMR)
-                        let balance_atomic = result.get("balance")
-                            .and_then(|b| b.as_u64())
-                            .unwrap_or(0);
-                        let balance_xmr = balance_atomic as f64 / 1_000_000_000_000.0;
-                        return Ok(balance_xmr);
-                    }
-                    if let Some(error) = data.get("error") {
-                        let msg = error.get("message")
-                            .and_then(|m| m.as_str())
-                            .unwrap_or("Erreur RPC inconnue");
-                        return Err(format!("Erreur wallet-rpc: {}", msg));
-                    }
+/// Nom de fichier stable du wallet sur le wallet-rpc, dérivé de l'adresse
+/// (l'adresse elle-même n'apparaît pas dans le répertoire du démon).
+fn watch_wallet_filename(address: &str) -> String {
+    let digest = sha256::hash(address.as_bytes());
+    format!("janus-watch-{}", &hex::encode(digest.as_ref())[..16])
+}
+
+/// Mot de passe du fichier wallet, dérivé de la view key pour ne pas laisser
+/// le fichier sans protection sur le wallet-rpc.
+fn watch_wallet_password(view_key: &str) -> String {
+    let digest = sha256::hash(format!("janus-monitor|{}", view_key.to_lowercase()).as_bytes());
+    hex::encode(digest.as_ref())
+}
+
+static WALLET_RPC_SESSIONS: once_cell::sync::Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Verrou d'un wallet-rpc: il n'a qu'un wallet ouvert à la fois, donc la séquence
+/// open_wallet → refresh → get_balance/get_transfers doit être tenue d'un bout à l'autre,
+/// sinon deux wallets rafraîchis ensemble lisent la balance l'un de l'autre.
+async fn lock_wallet_rpc(url: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    let key = url.trim().trim_end_matches('/').to_lowercase();
+    let session = WALLET_RPC_SESSIONS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key)
+        .or_default()
+        .clone();
+    session.lock_owned().await
+}
+
+fn piconero_to_xmr(atomic: u64) -> f64 {
+    atomic as f64 / PICONERO_PER_XMR
+}
+
+/// Ouvre le wallet de `data` sur le wallet-rpc, en le créant depuis les clés
+/// (generate_from_keys) s'il n'existe pas encore. Le refresh est laissé à l'appelant.
+async fn open_watch_wallet(rpc: &MoneroRpcClient, data: &MoneroWalletData) -> Result<(), MoneroError> {
+    let filename = watch_wallet_filename(&data.address);
+    let password = watch_wallet_password(&data.view_key);
+
+    let opened = rpc.call("open_wallet", Some(serde_json::json!({
+        "filename": filename,
+        "password": password,
+    }))).await;
+
+    match opened {
+        Ok(_) => {}
+        // Démon injoignable: inutile de tenter la création
+        Err(MoneroError::NodeConnectionFailed(e)) => return Err(MoneroError::NodeConnectionFailed(e)),
+        Err(_) => {
+            let mut params = serde_json::json!({
+                "restore_height": data.restore_height,
+                "filename": filename,
+                "address": data.address,
+                "viewkey": data.view_key,
+                "password": password,
+                "autosave_current": true,
+            });
+            if let Some(spend_key) = &data.spend_key {
+                params["spendkey"] = serde_json::Value::String(spend_key.clone());
+            }
+            // generate_from_keys laisse le nouveau wallet ouvert
+            rpc.call("generate_from_keys", Some(params)).await?;
+            secure_log("[Monero] Wallet watch-only créé", &filename);
+        }
+    }
+    Ok(())
+}
+
+/// Transferts entrants, sortants (sauf `incoming_only`) et en attente du wallet ouvert,
+/// les plus récents d'abord.
+async fn fetch_transfers(
+    rpc: &MoneroRpcClient,
+    limit: usize,
+    incoming_only: bool,
+    subaddress: Option<MoneroSubaddressIndex>,
+) -> Result<Vec<MoneroTransaction>, MoneroError> {
+    let mut params = serde_json::json!({
+        "in": true,
+        "out": !incoming_only,
+        "pending": true,
+        "pool": true,
+        "account_index": subaddress.map_or(0, |s| s.major)
+    });
+    if let Some(sub) = subaddress {
+        params["subaddr_indices"] = serde_json::json!([sub.minor]);
+    }
+    let result = rpc.call("get_transfers", Some(params)).await?;
+
+    let price = last_xmr_price();
+    let mut txs = Vec::new();
+    for direction in ["in", "out", "pending", "pool"] {
+        let Some(transfers) = result.get(direction).and_then(|t| t.as_array()) else { continue };
+        txs.extend(transfers.iter().map(|tx| parse_transfer(tx, direction, price)));
+    }
+
+    txs.sort_by_key(|tx| std::cmp::Reverse(tx.timestamp));
+    txs.truncate(limit);
+    Ok(txs)
+}
+
+/// (USD, EUR) d'un XMR au dernier get_prices
+fn last_xmr_price() -> Option<(f64, f64)> {
+    let last = LAST_PRICES.lock().ok()?;
+    let xmr = &last.as_ref()?.xmr;
+    (xmr.usd > 0.0 && xmr.eur > 0.0).then_some((xmr.usd, xmr.eur))
+}
+
+/// Entrée de get_transfers -> MoneroTransaction
+fn parse_transfer(tx: &serde_json::Value, direction: &str, price: Option<(f64, f64)>) -> MoneroTransaction {
+    let confirmations = tx.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0);
+    let amount_xmr = piconero_to_xmr(tx.get("amount").and_then(|a| a.as_u64()).unwrap_or(0));
+    // Identifiant de paiement nul = absent
+    let payment_id = tx.get("payment_id").and_then(|p| p.as_str())
+        .filter(|p| !p.is_empty() && p.chars().any(|c| c != '0'))
+        .map(str::to_string);
+    let subaddress_index = tx.get("subaddr_index").and_then(|i| Some(MoneroSubaddressIndex {
+        major: i.get("major")?.as_u64()? as u32,
+        minor: i.get("minor")?.as_u64()? as u32,
+    }));
+    MoneroTransaction {
+        hash: tx.get("txid").and_then(|t| t.as_str()).unwrap_or("").to_string(),
+        amount_xmr,
+        direction: direction.to_string(),
+        height: tx.get("height").and_then(|h| h.as_u64()).unwrap_or(0),
+        timestamp: tx.get("timestamp").and_then(|t| t.as_i64()).unwrap_or(0),
+        confirmations,
+        unlock_time: tx.get("unlock_time").and_then(|u| u.as_u64()).unwrap_or(0),
+        fee: piconero_to_xmr(tx.get("fee").and_then(|f| f.as_u64()).unwrap_or(0)),
+        // 10 blocs de verrouillage standard, sauf unlock_time explicite
+        unlocked: tx.get("locked").and_then(|l| l.as_bool()).map(|l| !l).unwrap_or(confirmations >= 10),
+        amount_eur: price.map(|(_, eur)| amount_xmr * eur),
+        amount_usd: price.map(|(usd, _)| amount_xmr * usd),
+        payment_id,
+        subaddress_index,
+    }
+}
+
+//
+// PROGRESSION DU SCAN
+//
+
+/// Intervalle entre deux lectures de get_height pendant un refresh
+const SCAN_PROGRESS_INTERVAL_SECS: u64 = 5;
+
+/// Avancement du dernier scan (refresh) d'un wallet, émis en `monero-scan-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct MoneroScanProgress {
+    pub wallet_id: i64,
+    pub restore_height: u64,
+    pub scanned_height: u64,
+    /// Hauteur du démon, inconnue avec un wallet-rpc explicite sans daemon_url
+    pub network_height: Option<u64>,
+    /// 0-100, None tant que la hauteur du réseau est inconnue
+    pub percent: Option<f64>,
+    pub done: bool,
+    pub updated_at: i64,
+}
+
+static SCAN_PROGRESS: once_cell::sync::Lazy<Mutex<HashMap<i64, MoneroScanProgress>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Destinataire de la progression du scan d'un wallet de la base
+pub(crate) struct ScanProgressSink {
+    app: AppHandle,
+    wallet_id: i64,
+}
+
+impl ScanProgressSink {
+    pub(crate) fn new(app: AppHandle, wallet_id: i64) -> Self {
+        Self { app, wallet_id }
+    }
+
+    fn report(&self, restore_height: u64, scanned_height: u64, network_height: Option<u64>, done: bool) {
+        let progress = MoneroScanProgress {
+            wallet_id: self.wallet_id,
+            restore_height,
+            scanned_height,
+            network_height,
+            percent: network_height.map(|h| scan_percent(restore_height, scanned_height, h)),
+            done,
+            updated_at: chrono::Utc::now().timestamp(),
+        };
+        if let Ok(mut all) = SCAN_PROGRESS.lock() {
+            all.insert(self.wallet_id, progress.clone());
+        }
+        self.app.emit("monero-scan-progress", &progress).ok();
+    }
+}
+
+/// Part des blocs entre restore_height et la hauteur du réseau déjà scannés
+fn scan_percent(restore_height: u64, scanned_height: u64, network_height: u64) -> f64 {
+    if network_height <= restore_height {
+        return 100.0;
+    }
+    let done = scanned_height.saturating_sub(restore_height) as f64;
+    (done / (network_height - restore_height) as f64 * 100.0).clamp(0.0, 100.0)
+}
+
+async fn wallet_height(rpc: &MoneroRpcClient) -> Result<u64, MoneroError> {
+    let height = rpc.call("get_height", None).await?;
+    Ok(height.get("height").and_then(|h| h.as_u64()).unwrap_or(0))
+}
+
+/// refresh du wallet ouvert. Avec `progress`, get_height est relu pendant le refresh
+/// (qui peut durer des heures depuis restore_height = 0) et chaque lecture est émise.
+async fn refresh_with_progress(
+    rpc: &MoneroRpcClient,
+    data: &MoneroWalletData,
+    network_height: Option<u64>,
+    progress: Option<&ScanProgressSink>,
+) -> Result<(), MoneroError> {
+    let Some(progress) = progress else {
+        return rpc.call("refresh", None).await.map(|_| ());
+    };
+    let refresh = rpc.call("refresh", None);
+    tokio::pin!(refresh);
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(SCAN_PROGRESS_INTERVAL_SECS));
+    loop {
+        tokio::select! {
+            result = &mut refresh => {
+                result?;
+                let scanned = wallet_height(rpc).await?;
+                progress.report(data.restore_height, scanned, network_height.map(|h| h.max(scanned)), true);
+                return Ok(());
+            }
+            _ = ticker.tick() => {
+                // Lecture best-effort: un wallet-rpc occupé ne doit pas retarder le refresh
+                let tick = tokio::time::timeout(std::time::Duration::from_secs(3), wallet_height(rpc)).await;
+                if let Ok(Ok(scanned)) = tick {
+                    progress.report(data.restore_height, scanned, network_height, false);
                 }
             }
-            Err("Réponse invalide du wallet-rpc Monero".to_string())
         }
-        Err(e) => Err(format!("Nœud wallet-rpc inaccessible: {}", e)),
     }
 }
 
-#[tauri::command]
-pub async fn get_monero_transactions(
-    _address: String,
-    _view_key: String,
-    _spend_key: Option<String>,
+/// get_info d'un démon: (hauteur, latence en ms)
+async fn probe_daemon(daemon_url: &str, allow_self_signed: bool) -> Result<(u64, u64), MoneroError> {
+    // Les identifiants RPC sont ceux du wallet-rpc: seul le réglage TLS est repris
+    let options = MoneroRpcOptions { allow_self_signed, ..Default::default() };
+    let rpc = MoneroRpcClient::new(daemon_url, 10, &options)?;
+    let started = std::time::Instant::now();
+    let info = rpc.call("get_info", None).await?;
+    let height = info.get("height").and_then(|h| h.as_u64())
+        .ok_or_else(|| MoneroError::RpcCallFailed("get_info: hauteur absente".to_string()))?;
+    Ok((height, started.elapsed().as_millis() as u64))
+}
+
+/// Ouvre le wallet et le synchronise. Avec un nœud explicite, le wallet-rpc garde son
+/// démon (`daemon_url` ne sert qu'à lire la hauteur du réseau); sinon les démons connus
+/// sont essayés du plus sain au moins sain via set_daemon. Renvoie la hauteur du réseau si connue.
+async fn sync_watch_wallet(
+    db: &DbState,
+    rpc: &MoneroRpcClient,
+    data: &MoneroWalletData,
+    explicit_node: bool,
+    daemon_url: Option<&str>,
+    progress: Option<&ScanProgressSink>,
+) -> Result<Option<u64>, MoneroError> {
+    open_watch_wallet(rpc, data).await?;
+    let allow_self_signed = rpc.options.allow_self_signed;
+
+    if explicit_node {
+        let network_height = match daemon_url {
+            Some(url) => probe_daemon(url, allow_self_signed).await.ok().map(|(height, _)| height),
+            None => None,
+        };
+        refresh_with_progress(rpc, data, network_height, progress).await?;
+        return Ok(network_height);
+    }
+
+    let candidates = {
+        let conn = db.0.lock().map_err(|e| MoneroError::RpcCallFailed(e.to_string()))?;
+        rank_nodes(load_monero_nodes(&conn).map_err(MoneroError::RpcCallFailed)?)
+    };
+    let mut last_error = MoneroError::NodeConnectionFailed("aucun nœud Monero actif".to_string());
+    for node in candidates.iter().filter(|n| n.enabled) {
+        let probe = probe_daemon(&node.url, allow_self_signed).await;
+        record_node_probe(db, &node.url, probe.as_ref().ok().copied());
+        let (height, _) = match probe {
+            Ok(p) => p,
+            Err(e) => {
+                log::warn!("[Monero] Nœud {} indisponible, bascule sur le suivant: {}", node.url, e);
+                last_error = e;
+                continue;
+            }
+        };
+        let synced = async {
+            rpc.call("set_daemon", Some(serde_json::json!({ "address": node.url, "trusted": false }))).await?;
+            refresh_with_progress(rpc, data, Some(height), progress).await
+        }.await;
+        match synced {
+            Ok(_) => return Ok(Some(height)),
+            // Le wallet-rpc lui-même ne répond plus: inutile d'essayer d'autres démons
+            Err(e @ MoneroError::NodeConnectionFailed(_)) => return Err(e),
+            Err(e) => {
+                log::warn!("[Monero] Refresh via {} échoué, bascule sur le suivant: {}", node.url, e);
+                record_node_probe(db, &node.url, None);
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+/// Balance du compte 0, ou de la seule sous-adresse `subaddress` via la ventilation
+/// `per_subaddress` de get_balance.
+async fn read_balance(
+    rpc: &MoneroRpcClient,
+    network_height: Option<u64>,
+    subaddress: Option<MoneroSubaddressIndex>,
+) -> Result<MoneroBalanceResult, MoneroError> {
+    let mut params = serde_json::json!({ "account_index": subaddress.map_or(0, |s| s.major) });
+    if let Some(sub) = subaddress {
+        params["address_indices"] = serde_json::json!([sub.minor]);
+    }
+    let balance = rpc.call("get_balance", Some(params)).await?;
+    let height = rpc.call("get_height", None).await?;
+
+    let last_scanned_height = height.get("height").and_then(|h| h.as_u64()).unwrap_or(0);
+    // Sans hauteur réseau connue: après refresh le wallet est à jour avec son démon
+    let network_height = network_height.unwrap_or(last_scanned_height);
+
+    let (amounts, subaddress_address) = match subaddress {
+        None => (&balance, None),
+        Some(sub) => {
+            let entry = subaddress_balance(&balance, sub.minor).ok_or_else(|| MoneroError::RpcCallFailed(format!(
+                "get_balance: sous-adresse {}/{} inconnue du wallet", sub.major, sub.minor
+            )))?;
+            (entry, entry.get("address").and_then(|a| a.as_str()).map(str::to_string))
+        }
+    };
+
+    Ok(MoneroBalanceResult {
+        balance: piconero_to_xmr(amounts.get("balance").and_then(|b| b.as_u64()).unwrap_or(0)),
+        unlocked_balance: piconero_to_xmr(amounts.get("unlocked_balance").and_then(|b| b.as_u64()).unwrap_or(0)),
+        last_scanned_height,
+        network_height: network_height.max(last_scanned_height),
+        transactions: fetch_transfers(rpc, 10, false, subaddress).await?,
+        subaddress_index: subaddress,
+        subaddress: subaddress_address,
+    })
+}
+
+/// Entrée `per_subaddress` de get_balance pour l'index `minor`
+fn subaddress_balance(balance: &serde_json::Value, minor: u32) -> Option<&serde_json::Value> {
+    balance.get("per_subaddress")?.as_array()?.iter()
+        .find(|entry| entry.get("address_index").and_then(|i| i.as_u64()) == Some(minor as u64))
+}
+
+fn wallet_data(
+    address: String,
+    view_key: String,
+    spend_key: Option<String>,
     node: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    // Monero wallet-rpc get_transfers
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(15))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let rpc_request = JsonRpcRequest {
-        jsonrpc: "2.0".to_string(),
-        id: "0".to_string(),
-        method: "get_transfers".to_string(),
-        params: Some(serde_json::json!({
-            "in": true,
-            "out": true,
-            "pending": true,
-            "account_index": 0
-        })),
+    restore_height: Option<u64>,
+    subaddress_index: Option<MoneroSubaddressIndex>,
+) -> Result<MoneroWalletData, String> {
+    let data = MoneroWalletData {
+        address: address.trim().to_string(),
+        view_key: view_key.trim().to_string(),
+        spend_key: spend_key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty()),
+        node: node.trim().to_string(),
+        restore_height: restore_height.unwrap_or(0),
+        subaddress_index,
     };
+    validate_monero_wallet_data(&data).map_err(|e| format!("Données wallet invalides: {}", e))?;
+    input_validation::validate_node_url(&data.node)?;
+    Ok(data)
+}
 
-    match client.post(format!("{}/json_rpc", node))
-        .json(&rpc_request)
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                if let Ok(data) = response.json::<serde_json::Value>().await {
-                    if let Some(result) = data.get("result") {
-                        let mut txs: Vec<serde_json::Value> = Vec::new();
-
-                        for direction in &["in", "out", "pending"] {
-                            if let Some(transfers) = result.get(direction).and_then(|t| t.as_array()) {
-                                for tx in transfers {
-                                    let amount_atomic = tx.get("amount")
-                                        .and_then(|a| a.as_u64())
-                                        .unwrap_or(0);
-                                    let amount_xmr = amount_atomic as f64 / 1_000_000_000_000.0;
-
-                                    txs.push(serde_json::json!({
-                                        "hash": tx.get("txid").and_then(|t| t.as_str()).unwrap_or(""),
-                                        "amount": amount_xmr,
-                                        "direction": direction,
-                                        "height": tx.get("height").and_then(|h| h.as_u64()).unwrap_or(0),
-                                        "timestamp": tx.get("timestamp").and_then(|t| t.as_u64()).unwrap_or(0),
-                                        "confirmations": tx.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0),
-                                    }));
-                                }
-                            }
-                        }
-
-                        // Sort by timestamp descending, take last 10
-                        txs.sort_by(|a, b| {
-                            let ta = a.get("timestamp").and_then(|t| t.as_u64()).unwrap_or(0);
-                            let tb = b.get("timestamp").and_then(|t| t.as_u64()).unwrap_or(0);
-                            tb.cmp(&ta)
-                        });
-                        txs.truncate(10);
-
-                        return Ok(txs);
-                    }
-                }
+/// Nœud explicite (node_url du wallet) ou, à défaut, le wallet-rpc local
+/// (`monero_wallet_rpc_url`) relié aux démons connus.
+fn resolve_wallet_rpc(db: &DbState, node: Option<String>) -> Result<(String, bool), String> {
+    if let Some(node) = node.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        return Ok((node, true));
+    }
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let url = read_setting(&conn, "monero_wallet_rpc_url")
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_WALLET_RPC_URL.to_string());
+    Ok((url, false))
+}
+
+/// Confirmations d'un transfert à la hauteur `network_height` (0 tant qu'il est dans le mempool)
+fn confirmations_at(tx_height: u64, network_height: u64) -> u64 {
+    if tx_height == 0 || network_height < tx_height {
+        0
+    } else {
+        network_height - tx_height + 1
+    }
+}
+
+/// Transferts entrants récents d'un wallet pour le monitoring en arrière-plan: même
+/// résolution du nœud et même failover que get_monero_balance, confirmations recalculées
+/// sur la hauteur du démon quand elle est connue.
+pub(crate) async fn monitor_incoming_transfers(
+    db: &DbState,
+    data: MoneroWalletData,
+    node: Option<String>,
+    options: &MoneroRpcOptions,
+    progress: Option<ScanProgressSink>,
+) -> Result<Vec<MoneroTransaction>, String> {
+    let (url, explicit) = resolve_wallet_rpc(db, node)?;
+    let data = wallet_data(data.address, data.view_key, data.spend_key, url, Some(data.restore_height), data.subaddress_index)?;
+    let transfers = async {
+        let rpc = MoneroRpcClient::new(&data.node, 300, options)?;
+        let _session = lock_wallet_rpc(&data.node).await;
+        let network_height = sync_watch_wallet(db, &rpc, &data, explicit, None, progress.as_ref()).await?;
+        let mut txs = fetch_transfers(&rpc, 20, true, data.subaddress_index).await?;
+        if let Some(height) = network_height {
+            for tx in &mut txs {
+                tx.confirmations = confirmations_at(tx.height, height);
             }
-            Err("Réponse invalide du wallet-rpc Monero".to_string())
         }
-        Err(e) => Err(format!("Nœud wallet-rpc inaccessible: {}", e)),
+        Ok::<_, MoneroError>(txs)
+    }.await;
+    transfers.map_err(|e| e.to_string())
+}
+
+//
+// NŒUDS CONNUS ET FAILOVER
+//
+
+/// wallet-rpc utilisé quand le wallet n'a pas de nœud explicite
+const DEFAULT_WALLET_RPC_URL: &str = "http://127.0.0.1:18082";
+
+/// Au-delà de ce retard sur le meilleur nœud, un démon est considéré désynchronisé
+const MAX_HEIGHT_LAG: u64 = 10;
+
+/// Démons publics insérés dans monero_nodes à la création de la base
+pub fn get_default_monero_nodes() -> Vec<String> {
+    vec![
+        "http://node.monerooutreach.org:18089".to_string(),
+        "http://xmr-node.cakewallet.com:18089".to_string(),
+        "http://node.supportxmr.com:18089".to_string(),
+    ]
+}
+
+/// Ligne de la table monero_nodes. `last_latency_ms` est None quand le dernier test a échoué.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MoneroNodeHealth {
+    pub url: String,
+    pub last_height: Option<u64>,
+    pub last_latency_ms: Option<u64>,
+    pub last_ok_at: Option<String>,
+    pub enabled: bool,
+}
+
+fn load_monero_nodes(conn: &Connection) -> Result<Vec<MoneroNodeHealth>, String> {
+    conn.prepare("SELECT url, last_height, last_latency_ms, last_ok_at, enabled FROM monero_nodes")
+        .and_then(|mut stmt| stmt.query_map([], |row| Ok(MoneroNodeHealth {
+            url: row.get(0)?,
+            last_height: row.get::<_, Option<i64>>(1)?.map(|h| h as u64),
+            last_latency_ms: row.get::<_, Option<i64>>(2)?.map(|l| l as u64),
+            last_ok_at: row.get(3)?,
+            enabled: row.get::<_, i64>(4)? != 0,
+        }))?.collect())
+        .map_err(|e| e.to_string())
+}
+
+/// Du plus sain au moins sain: nœuds actifs qui ont répondu et sont à jour (par latence),
+/// puis ceux en retard, puis les nœuds en échec ou jamais testés (dernier succès le plus récent d'abord).
+fn rank_nodes(mut nodes: Vec<MoneroNodeHealth>) -> Vec<MoneroNodeHealth> {
+    let best_height = nodes.iter()
+        .filter(|n| n.last_latency_ms.is_some())
+        .filter_map(|n| n.last_height)
+        .max()
+        .unwrap_or(0);
+    let tier = |n: &MoneroNodeHealth| match (n.enabled, n.last_latency_ms, n.last_height) {
+        (false, _, _) => 3,
+        (true, Some(_), Some(h)) if h + MAX_HEIGHT_LAG >= best_height => 0,
+        (true, Some(_), _) => 1,
+        (true, None, _) => 2,
+    };
+    nodes.sort_by(|a, b| {
+        tier(a).cmp(&tier(b))
+            .then(a.last_latency_ms.unwrap_or(u64::MAX).cmp(&b.last_latency_ms.unwrap_or(u64::MAX)))
+            .then(b.last_ok_at.cmp(&a.last_ok_at))
+            .then(a.url.cmp(&b.url))
+    });
+    nodes
+}
+
+/// Enregistre le résultat d'un test: (hauteur, latence) ou None en cas d'échec
+fn record_node_probe(db: &DbState, url: &str, result: Option<(u64, u64)>) {
+    let Ok(conn) = db.0.lock() else { return };
+    let updated = match result {
+        Some((height, latency)) => conn.execute(
+            "UPDATE monero_nodes SET last_height = ?1, last_latency_ms = ?2, last_ok_at = CURRENT_TIMESTAMP WHERE url = ?3",
+            params![height as i64, latency as i64, url],
+        ),
+        None => conn.execute("UPDATE monero_nodes SET last_latency_ms = NULL WHERE url = ?1", params![url]),
+    };
+    if let Err(e) = updated {
+        log::warn!("[Monero] Santé du nœud non enregistrée: {}", e);
+    }
+}
+
+//
+// COMMANDES TAURI - MONERO
+//
+
+#[tauri::command]
+pub async fn test_monero_node(
+    node_url: String,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
+    allow_self_signed: Option<bool>,
+) -> Result<MoneroNodeInfo, String> {
+    let node_url = node_url.trim().to_string();
+    input_validation::validate_node_url(&node_url)?;
+    let options = MoneroRpcOptions::new(rpc_user, rpc_password, allow_self_signed);
+    let rpc = MoneroRpcClient::new(&node_url, 10, &options).map_err(|e| e.to_string())?;
+    let via_proxy = rpc.via_proxy;
+
+    match rpc.call("get_info", None).await {
+        Ok(result) => Ok(MoneroNodeInfo {
+            url: node_url,
+            height: result.get("height").and_then(|h| h.as_u64()).unwrap_or(0),
+            success: true,
+            error: None,
+            via_proxy,
+        }),
+        Err(MoneroError::NodeConnectionFailed(e)) => Ok(MoneroNodeInfo {
+            url: node_url,
+            height: 0,
+            success: false,
+            error: Some(format!("Nœud inaccessible: {}", e)),
+            via_proxy,
+        }),
+        Err(_) => Ok(MoneroNodeInfo {
+            url: node_url,
+            height: 0,
+            success: false,
+            error: Some("Réponse invalide du nœud".to_string()),
+            via_proxy,
+        }),
+    }
+}
+
+/// Teste tous les démons actifs de monero_nodes (get_info), enregistre hauteur et latence,
+/// et renvoie la liste classée du plus sain au moins sain.
+#[tauri::command]
+pub async fn rank_monero_nodes(state: State<'_, DbState>) -> Result<Vec<MoneroNodeHealth>, String> {
+    let urls: Vec<String> = {
+        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        load_monero_nodes(&conn)?.into_iter().filter(|n| n.enabled).map(|n| n.url).collect()
+    };
+
+    let mut probes = tokio::task::JoinSet::new();
+    for url in urls {
+        probes.spawn(async move {
+            let result = probe_daemon(&url, false).await.ok();
+            (url, result)
+        });
+    }
+    while let Some(joined) = probes.join_next().await {
+        if let Ok((url, result)) = joined {
+            record_node_probe(&state, &url, result);
+        }
+    }
+
+    let conn = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(rank_nodes(load_monero_nodes(&conn)?))
+}
+
+/// Balance du wallet `address` scannée par monero-wallet-rpc avec la view key.
+/// `node` (node_url du wallet) est prioritaire; sans lui, le wallet-rpc local passe par
+/// le démon le plus sain de monero_nodes et bascule sur le suivant en cas d'échec.
+/// `daemon_url` (optionnel) sert à lire la hauteur du réseau avec un nœud explicite.
+/// `subaddress_index` (compte, index) restreint la balance à une sous-adresse.
+/// Avec `wallet_id`, l'avancement du refresh est émis en `monero-scan-progress`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_monero_balance(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    wallet_id: Option<i64>,
+    address: String,
+    view_key: String,
+    spend_key: Option<String>,
+    node: Option<String>,
+    restore_height: Option<u64>,
+    daemon_url: Option<String>,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
+    allow_self_signed: Option<bool>,
+    subaddress_index: Option<MoneroSubaddressIndex>,
+) -> Result<MoneroBalanceResult, String> {
+    let options = MoneroRpcOptions::new(rpc_user, rpc_password, allow_self_signed);
+    let (node, explicit) = resolve_wallet_rpc(&state, node)?;
+    let data = wallet_data(address, view_key, spend_key, node, restore_height, subaddress_index)?;
+    log_address("Monero", &data.address);
+
+    let balance = async {
+        // Le refresh initial d'un wallet restauré peut être long
+        let rpc = MoneroRpcClient::new(&data.node, 300, &options)?;
+        let _session = lock_wallet_rpc(&data.node).await;
+        let progress = wallet_id.map(|id| ScanProgressSink::new(app.clone(), id));
+        let daemon_url = daemon_url.as_deref().filter(|u| !u.is_empty());
+        let network_height = sync_watch_wallet(&state, &rpc, &data, explicit, daemon_url, progress.as_ref()).await?;
+        read_balance(&rpc, network_height, data.subaddress_index).await
+    }.await;
+    let result = balance.map_err(|e| format!("Erreur balance Monero: {}", e))?;
+    log_balance("Monero", result.balance);
+    Ok(result)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_monero_transactions(
+    state: State<'_, DbState>,
+    address: String,
+    view_key: String,
+    spend_key: Option<String>,
+    node: Option<String>,
+    limit: Option<usize>,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
+    allow_self_signed: Option<bool>,
+    subaddress_index: Option<MoneroSubaddressIndex>,
+) -> Result<Vec<MoneroTransaction>, String> {
+    let options = MoneroRpcOptions::new(rpc_user, rpc_password, allow_self_signed);
+    let (node, explicit) = resolve_wallet_rpc(&state, node)?;
+    let data = wallet_data(address, view_key, spend_key, node, None, subaddress_index)?;
+    log_address("Monero", &data.address);
+
+    let transfers = async {
+        let rpc = MoneroRpcClient::new(&data.node, 300, &options)?;
+        let _session = lock_wallet_rpc(&data.node).await;
+        sync_watch_wallet(&state, &rpc, &data, explicit, None, None).await?;
+        fetch_transfers(&rpc, limit.unwrap_or(10), false, data.subaddress_index).await
+    }.await;
+    transfers.map_err(|e| format!("Erreur historique Monero: {}", e))
+}
+
+/// Dernier avancement connu du scan d'un wallet depuis le lancement (None = aucun scan suivi)
+#[tauri::command]
+pub fn get_monero_scan_progress(wallet_id: i64) -> Result<Option<MoneroScanProgress>, String> {
+    let all = SCAN_PROGRESS.lock().map_err(|e| e.to_string())?;
+    Ok(all.get(&wallet_id).cloned())
+}
+
+//
+// TESTS UNITAIRES
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A";
+    const VIEW_KEY: &str = "a1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5f6a1b2c3d4e5f6a1b2";
+
+    #[test]
+    fn test_validate_monero_address() {
+        // Adresse valide
+        assert!(validate_monero_address(ADDRESS).is_ok());
+
+        // Adresse trop courte
+        assert!(validate_monero_address(&ADDRESS[..94]).is_err());
+
+        // Adresse ne commençant pas par 4
+        assert!(validate_monero_address(&format!("5{}", &ADDRESS[1..])).is_err());
+
+        // Sous-adresse et adresse intégrée acceptées, mais pas pour créer le wallet watch-only
+        let subaddress = format!("8{}", &ADDRESS[1..]);
+        assert!(validate_monero_address(&subaddress).is_ok());
+        assert!(validate_monero_address(&format!("{}AbCdEfGhJkL", ADDRESS)).is_ok());
+        assert!(wallet_data(subaddress, VIEW_KEY.into(), None, "http://127.0.0.1:18083".into(), None, None).is_err());
+
+        // Caractère hors base58
+        assert!(validate_monero_address(&format!("{}0", &ADDRESS[..94])).is_err());
+    }
+
+    #[test]
+    fn test_validate_view_key() {
+        assert!(validate_view_key(VIEW_KEY).is_ok());
+        assert!(validate_view_key(&VIEW_KEY[..63]).is_err());
+        assert!(validate_view_key(&format!("{}g!", &VIEW_KEY[..62])).is_err());
+        assert!(validate_spend_key(&None).is_ok());
+        assert!(validate_spend_key(&Some("abc".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_wallet_data_requires_node() {
+        assert!(wallet_data(ADDRESS.into(), VIEW_KEY.into(), Some(String::new()), "http://127.0.0.1:18083".into(), None, None).is_ok());
+        assert!(wallet_data(ADDRESS.into(), VIEW_KEY.into(), None, "  ".into(), None, None).is_err());
+    }
+
+    #[test]
+    fn test_digest_challenge_and_response() {
+        let challenge = parse_digest_challenge(
+            "Digest qop=\"auth\",algorithm=MD5,realm=\"monero-rpc\",nonce=\"5wz2DyTXsXQ9hNQQ7Yr2Lw==\",stale=false"
+        ).unwrap();
+        assert_eq!(challenge.realm, "monero-rpc");
+        assert!(challenge.qop_auth && !challenge.sess);
+        assert!(parse_digest_challenge("Basic realm=\"x\"").is_none());
+        assert!(parse_digest_challenge("Digest realm=\"x\", nonce=\"n\", algorithm=SHA-256").is_none());
+
+        // RFC 2617 §3.5
+        let rfc = DigestChallenge {
+            realm: "testrealm@host.com".into(),
+            nonce: "dcd98b7102dd2f0e8b11d0f600bfb0c093".into(),
+            opaque: Some("5ccc069c403ebaf9f0171e9517f40e41".into()),
+            qop_auth: true,
+            sess: false,
+            nonce_count: 1,
+        };
+        let header = digest_authorization(&rfc, "Mufasa", "Circle Of Life", "GET", "/dir/index.html", "0a4f113b");
+        assert!(header.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+        assert!(header.contains("nc=00000001") && header.contains("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""));
+    }
+
+    #[test]
+    fn test_rank_nodes_prefers_healthy_synced_nodes() {
+        let node = |url: &str, height: Option<u64>, latency: Option<u64>, ok: Option<&str>, enabled: bool| MoneroNodeHealth {
+            url: url.into(),
+            last_height: height,
+            last_latency_ms: latency,
+            last_ok_at: ok.map(str::to_string),
+            enabled,
+        };
+        let ranked = rank_nodes(vec![
+            node("down", Some(3_200_000), None, Some("2026-10-01 10:00:00"), true),
+            node("lagging", Some(3_199_000), Some(20), Some("2026-10-15 10:00:00"), true),
+            node("slow", Some(3_200_001), Some(900), Some("2026-10-15 10:00:00"), true),
+            node("disabled", Some(3_200_001), Some(5), Some("2026-10-15 10:00:00"), false),
+            node("fast", Some(3_199_995), Some(80), Some("2026-10-15 10:00:00"), true),
+            node("never", None, None, None, true),
+        ]);
+        let order: Vec<&str> = ranked.iter().map(|n| n.url.as_str()).collect();
+        assert_eq!(order, ["fast", "slow", "lagging", "down", "never", "disabled"]);
+    }
+
+    #[test]
+    fn test_parse_transfer_keeps_json_keys() {
+        let raw = serde_json::json!({
+            "txid": "c3f2", "amount": 2_500_000_000_000u64, "fee": 30_000_000u64, "height": 3_200_000,
+            "timestamp": 1_760_000_000, "confirmations": 4, "unlock_time": 0, "locked": true,
+            "payment_id": "0000000000000000", "subaddr_index": { "major": 0, "minor": 3 }
+        });
+        let tx = parse_transfer(&raw, "in", Some((150.0, 140.0)));
+        assert!(tx.is_incoming() && !tx.unlocked);
+        assert_eq!(tx.payment_id, None);
+        assert_eq!(tx.subaddress_index, Some(MoneroSubaddressIndex { major: 0, minor: 3 }));
+        assert_eq!((tx.amount_usd, tx.amount_eur), (Some(375.0), Some(350.0)));
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(json["hash"], "c3f2");
+        assert_eq!(json["amount"], 2.5);
+        assert_eq!(json["direction"], "in");
+        assert!((json["fee"].as_f64().unwrap() - 0.00003).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_subaddress_balance_entry() {
+        let balance = serde_json::json!({
+            "balance": 5_000_000_000_000u64,
+            "per_subaddress": [
+                { "address_index": 0, "address": "4...", "balance": 4_000_000_000_000u64 },
+                { "address_index": 3, "address": "8...", "balance": 1_000_000_000_000u64, "unlocked_balance": 0 }
+            ]
+        });
+        let entry = subaddress_balance(&balance, 3).unwrap();
+        assert_eq!(entry["address"], "8...");
+        assert!(subaddress_balance(&balance, 7).is_none());
+        let index: MoneroSubaddressIndex = serde_json::from_value(serde_json::json!({ "account": 1, "index": 3 })).unwrap();
+        assert_eq!(index, MoneroSubaddressIndex { major: 1, minor: 3 });
+    }
+
+    #[test]
+    fn test_scan_percent_from_restore_height() {
+        assert_eq!(scan_percent(3_000_000, 3_000_000, 3_200_000), 0.0);
+        assert_eq!(scan_percent(3_000_000, 3_150_000, 3_200_000), 75.0);
+        assert_eq!(scan_percent(3_000_000, 3_200_004, 3_200_000), 100.0);
+        // Wallet créé au-dessus de la hauteur courante: rien à scanner
+        assert_eq!(scan_percent(3_300_000, 0, 3_200_000), 100.0);
+    }
+
+    #[test]
+    fn test_confirmations_from_daemon_height() {
+        assert_eq!(confirmations_at(0, 3_200_000), 0);
+        assert_eq!(confirmations_at(3_200_000, 3_200_000), 1);
+        assert_eq!(confirmations_at(3_199_991, 3_200_000), 10);
+        assert_eq!(confirmations_at(3_200_005, 3_200_000), 0);
+    }
+
+    #[test]
+    fn test_watch_wallet_naming() {
+        let name = watch_wallet_filename(ADDRESS);
+        assert_eq!(name, watch_wallet_filename(ADDRESS));
+        assert!(name.starts_with("janus-watch-") && !name.contains(&ADDRESS[..10]));
+        assert_ne!(watch_wallet_password(VIEW_KEY), watch_wallet_password(&VIEW_KEY.to_uppercase()[..63]));
+        assert_eq!(watch_wallet_password(VIEW_KEY), watch_wallet_password(&VIEW_KEY.to_uppercase()));
+        assert_eq!(piconero_to_xmr(1_500_000_000_000), 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_wallet_rpc_sessions_are_serialised_per_url() {
+        let held = lock_wallet_rpc("http://127.0.0.1:28082/").await;
+        // Même wallet-rpc, autre orthographe: doit attendre la session en cours
+        let same = tokio::time::timeout(std::time::Duration::from_millis(50), lock_wallet_rpc("HTTP://127.0.0.1:28082")).await;
+        assert!(same.is_err());
+        // Un autre wallet-rpc n'est pas bloqué
+        let other = tokio::time::timeout(std::time::Duration::from_millis(50), lock_wallet_rpc("http://127.0.0.1:28083")).await;
+        assert!(other.is_ok());
+        drop(held);
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), lock_wallet_rpc("http://127.0.0.1:28082")).await.is_ok());
     }
 }
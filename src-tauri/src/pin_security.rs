@@ -3,17 +3,22 @@
 // =============================================================================
 // Argon2id hashing, rate limiting, timing-safe comparison, legacy migration
 // FIXES: CRIT-01, CRIT-11, MAJ-05
+// Rate-limit state is written through to the `auth_rate_limit` table so that
+// relaunching the app does not reset a lockout.
 // =============================================================================
 
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2, Algorithm, Version, Params,
 };
+use rusqlite::params;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
 
-const MAX_FAILED_ATTEMPTS: u32 = 10;
+pub const MAX_FAILED_ATTEMPTS: u32 = 10;
 const BASE_DELAY_MS: u64 = 1000;
 const MAX_DELAY_MS: u64 = 300_000;
 const LOCKOUT_DURATION_SECS: u64 = 900;
@@ -21,25 +26,95 @@ const ARGON2_M_COST: u32 = 65536;
 const ARGON2_T_COST: u32 = 3;
 const ARGON2_P_COST: u32 = 4;
 
+// Wall-clock times: unlike Instant they survive a restart
 pub struct RateLimitEntry {
     pub failed_attempts: u32,
-    pub last_attempt: Instant,
-    pub locked_until: Option<Instant>,
+    pub last_attempt: SystemTime,
+    pub locked_until: Option<SystemTime>,
 }
 
 impl Default for RateLimitEntry {
     fn default() -> Self {
         Self {
             failed_attempts: 0,
-            last_attempt: Instant::now(),
+            last_attempt: SystemTime::now(),
             locked_until: None,
         }
     }
 }
 
+/// Where a profile stands with the limiter. Wording is up to the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RateLimitStatus {
+    pub failed_attempts: u32,
+    /// Lockout after MAX_FAILED_ATTEMPTS (as opposed to the per-attempt cooldown)
+    pub is_locked: bool,
+    /// Unix time (seconds) the lockout ends
+    pub locked_until_secs: Option<u64>,
+    /// Seconds before the next attempt is accepted (0 = now)
+    pub next_retry_secs: u64,
+}
+
 lazy_static::lazy_static! {
     static ref RATE_LIMIT_STATE: Mutex<HashMap<String, RateLimitEntry>> =
         Mutex::new(HashMap::new());
+    // Database the state is written through to (set by init_persistence)
+    static ref RATE_LIMIT_DB: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+fn to_unix_ms(t: SystemTime) -> i64 {
+    t.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+fn from_unix_ms(ms: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(ms.max(0) as u64)
+}
+
+/// Entries stored in the `auth_rate_limit` table of `db_path`
+fn load_entries(db_path: &Path) -> Result<Vec<(String, RateLimitEntry)>, String> {
+    let conn = crate::open_db(db_path).map_err(|e| e.to_string())?;
+    let rows: Vec<(String, u32, i64, Option<i64>)> = conn
+        .prepare("SELECT profile_name, failed_attempts, last_attempt, locked_until FROM auth_rate_limit")
+        .and_then(|mut stmt| stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?.collect())
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().map(|(profile_name, failed_attempts, last_attempt, locked_until)| {
+        (profile_name, RateLimitEntry {
+            failed_attempts,
+            last_attempt: from_unix_ms(last_attempt),
+            locked_until: locked_until.map(from_unix_ms),
+        })
+    }).collect())
+}
+
+/// Store (or, with None, delete) one profile's entry in `db_path`
+fn write_entry(db_path: &Path, profile_name: &str, entry: Option<&RateLimitEntry>) -> rusqlite::Result<usize> {
+    let conn = crate::open_db(db_path)?;
+    match entry {
+        Some(e) => conn.execute(
+            "INSERT OR REPLACE INTO auth_rate_limit (profile_name, failed_attempts, last_attempt, locked_until) VALUES (?1, ?2, ?3, ?4)",
+            params![profile_name, e.failed_attempts, to_unix_ms(e.last_attempt), e.locked_until.map(to_unix_ms)],
+        ),
+        None => conn.execute("DELETE FROM auth_rate_limit WHERE profile_name = ?1", params![profile_name]),
+    }
+}
+
+/// Load the persisted rate-limit state from `db_path` (which must hold the `auth_rate_limit`
+/// table) and write every later change through to it. Returns the number of profiles loaded.
+pub fn init_persistence(db_path: &Path) -> Result<usize, String> {
+    let entries = load_entries(db_path)?;
+    let loaded = entries.len();
+    RATE_LIMIT_STATE.lock().map_err(|e| format!("Lock error: {}", e))?.extend(entries);
+    *RATE_LIMIT_DB.lock().map_err(|e| format!("Lock error: {}", e))? = Some(db_path.to_path_buf());
+    Ok(loaded)
+}
+
+/// Write-through of one profile's entry (None = forget it). Failures are logged only:
+/// the in-memory limiter keeps working.
+fn persist_entry(profile_name: &str, entry: Option<&RateLimitEntry>) {
+    let Some(path) = RATE_LIMIT_DB.lock().ok().and_then(|p| p.clone()) else { return };
+    if let Err(e) = write_entry(&path, profile_name, entry) {
+        log::warn!("[SECURITY] Could not persist rate limit for '{}': {}", profile_name, e);
+    }
 }
 
 fn get_argon2_hasher() -> Argon2<'static> {
@@ -82,27 +157,188 @@ pub fn verify_pin(raw_pin: &str, stored_hash: &str) -> Result<bool, String> {
     }
 }
 
-pub fn check_rate_limit(profile_name: &str) -> Result<(), String> {
-    let mut state = RATE_LIMIT_STATE.lock().map_err(|e| format!("Lock error: {}", e))?;
-    let entry = state.entry(profile_name.to_string()).or_insert_with(RateLimitEntry::default);
+// =============================================================================
+// CREDENTIAL POLICY (settings pin_min_length, password_min_length, password_require_*)
+// Only applied when a PIN/password is set or changed.
+// =============================================================================
 
-    if let Some(locked_until) = entry.locked_until {
-        if Instant::now() < locked_until {
-            let remaining = locked_until.duration_since(Instant::now());
-            return Err(format!("Profil verrouillé. Réessayez dans {} secondes.", remaining.as_secs()));
-        } else {
-            entry.locked_until = None;
-            entry.failed_attempts = 0;
+const MIN_PIN_LENGTH: usize = 4;
+const MAX_CREDENTIAL_LENGTH: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialKind {
+    Pin,
+    Password,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialPolicy {
+    pub pin_min_length: usize,
+    pub password_min_length: usize,
+    pub password_require_mixed_case: bool,
+    pub password_require_digit: bool,
+    pub password_require_symbol: bool,
+}
+
+impl Default for CredentialPolicy {
+    fn default() -> Self {
+        Self {
+            pin_min_length: MIN_PIN_LENGTH,
+            password_min_length: 8,
+            password_require_mixed_case: false,
+            password_require_digit: false,
+            password_require_symbol: false,
+        }
+    }
+}
+
+impl CredentialPolicy {
+    /// Policy from the settings table; missing or invalid values keep the defaults.
+    /// Minimums never go below the built-in ones.
+    pub fn from_settings(conn: &rusqlite::Connection) -> Self {
+        let default = Self::default();
+        let length = |key: &str, min: usize| {
+            crate::read_setting(conn, key)
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .map_or(min, |n| n.clamp(min, MAX_CREDENTIAL_LENGTH))
+        };
+        let flag = |key: &str| crate::read_setting(conn, key).as_deref() == Some("true");
+        Self {
+            pin_min_length: length("pin_min_length", default.pin_min_length),
+            password_min_length: length("password_min_length", default.password_min_length),
+            password_require_mixed_case: flag("password_require_mixed_case"),
+            password_require_digit: flag("password_require_digit"),
+            password_require_symbol: flag("password_require_symbol"),
         }
     }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PolicyRuleResult {
+    /// min_length, max_length, mixed_case, digit, symbol
+    pub rule: &'static str,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CredentialStrength {
+    pub rules: Vec<PolicyRuleResult>,
+    pub passed: bool,
+    /// log2(character pool) × length: an upper bound, dictionary words score too high
+    pub entropy_bits: f64,
+}
+
+fn estimate_entropy(value: &str) -> f64 {
+    let has = |f: fn(&char) -> bool| value.chars().any(|c| f(&c));
+    let mut pool = 0u32;
+    if has(char::is_ascii_lowercase) { pool += 26; }
+    if has(char::is_ascii_uppercase) { pool += 26; }
+    if has(char::is_ascii_digit) { pool += 10; }
+    if has(char::is_ascii_punctuation) || value.contains(' ') { pool += 33; }
+    if has(|c| !c.is_ascii()) { pool += 100; }
+    if pool == 0 {
+        return 0.0;
+    }
+    (pool as f64).log2() * value.chars().count() as f64
+}
 
-    if entry.failed_attempts > 0 {
-        let delay_ms = calculate_delay(entry.failed_attempts);
-        let elapsed = entry.last_attempt.elapsed();
-        if elapsed < Duration::from_millis(delay_ms) {
-            let remaining = Duration::from_millis(delay_ms) - elapsed;
-            return Err(format!("Trop de tentatives. Réessayez dans {} secondes.", remaining.as_secs() + 1));
+/// Every rule of `policy` that applies to `kind`, with its outcome for `value`
+pub fn evaluate_credential(kind: CredentialKind, value: &str, policy: &CredentialPolicy) -> CredentialStrength {
+    let len = value.chars().count();
+    let min_length = match kind {
+        CredentialKind::Pin => policy.pin_min_length,
+        CredentialKind::Password => policy.password_min_length,
+    };
+    let mut rules = vec![
+        PolicyRuleResult { rule: "min_length", passed: len >= min_length },
+        PolicyRuleResult { rule: "max_length", passed: len <= MAX_CREDENTIAL_LENGTH },
+    ];
+    if kind == CredentialKind::Password {
+        if policy.password_require_mixed_case {
+            let mixed = value.chars().any(char::is_lowercase) && value.chars().any(char::is_uppercase);
+            rules.push(PolicyRuleResult { rule: "mixed_case", passed: mixed });
+        }
+        if policy.password_require_digit {
+            rules.push(PolicyRuleResult { rule: "digit", passed: value.chars().any(|c| c.is_ascii_digit()) });
         }
+        if policy.password_require_symbol {
+            rules.push(PolicyRuleResult { rule: "symbol", passed: value.chars().any(|c| !c.is_alphanumeric()) });
+        }
+    }
+    CredentialStrength {
+        passed: rules.iter().all(|r| r.passed),
+        rules,
+        entropy_bits: estimate_entropy(value),
+    }
+}
+
+/// Err with the first rule `value` breaks
+pub fn validate_policy(kind: CredentialKind, value: &str, policy: &CredentialPolicy) -> Result<(), String> {
+    let what = match kind {
+        CredentialKind::Pin => "Le PIN",
+        CredentialKind::Password => "Le mot de passe",
+    };
+    let strength = evaluate_credential(kind, value, policy);
+    match strength.rules.iter().find(|r| !r.passed).map(|r| r.rule) {
+        None => Ok(()),
+        Some("min_length") => Err(format!("{} doit contenir au moins {} caractères", what, match kind {
+            CredentialKind::Pin => policy.pin_min_length,
+            CredentialKind::Password => policy.password_min_length,
+        })),
+        Some("max_length") => Err(format!("{} est trop long (max {} caractères)", what, MAX_CREDENTIAL_LENGTH)),
+        Some("mixed_case") => Err(format!("{} doit mélanger majuscules et minuscules", what)),
+        Some("digit") => Err(format!("{} doit contenir un chiffre", what)),
+        Some(_) => Err(format!("{} doit contenir un symbole", what)),
+    }
+}
+
+/// Whole seconds, rounded up so that "retry in 0 s" is never shown while still refused
+fn ceil_secs(d: Duration) -> u64 {
+    d.as_secs() + u64::from(d.subsec_nanos() > 0)
+}
+
+fn status_of(entry: &RateLimitEntry, now: SystemTime) -> RateLimitStatus {
+    if let Some(locked_until) = entry.locked_until {
+        return match locked_until.duration_since(now) {
+            Ok(remaining) => RateLimitStatus {
+                failed_attempts: entry.failed_attempts,
+                is_locked: true,
+                locked_until_secs: Some(to_unix_ms(locked_until) as u64 / 1000),
+                next_retry_secs: ceil_secs(remaining),
+            },
+            // Expired lockout: the counter starts over
+            Err(_) => RateLimitStatus::default(),
+        };
+    }
+    let delay = Duration::from_millis(calculate_delay(entry.failed_attempts));
+    let elapsed = now.duration_since(entry.last_attempt).unwrap_or(Duration::ZERO);
+    RateLimitStatus {
+        failed_attempts: entry.failed_attempts,
+        is_locked: false,
+        locked_until_secs: None,
+        next_retry_secs: delay.checked_sub(elapsed).map(ceil_secs).unwrap_or(0),
+    }
+}
+
+pub fn get_rate_limit_status(profile_name: &str) -> RateLimitStatus {
+    let state = RATE_LIMIT_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.get(profile_name).map(|e| status_of(e, SystemTime::now())).unwrap_or_default()
+}
+
+/// Err(status) while the profile must wait (cooldown or lockout).
+pub fn check_rate_limit(profile_name: &str) -> Result<(), RateLimitStatus> {
+    let mut state = RATE_LIMIT_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(entry) = state.get_mut(profile_name) else { return Ok(()) };
+    let now = SystemTime::now();
+    if entry.locked_until.is_some_and(|t| t <= now) {
+        entry.locked_until = None;
+        entry.failed_attempts = 0;
+        persist_entry(profile_name, None);
+    }
+    let status = status_of(entry, now);
+    if status.next_retry_secs > 0 {
+        return Err(status);
     }
     Ok(())
 }
@@ -110,13 +346,8 @@ pub fn check_rate_limit(profile_name: &str) -> Result<(), String> {
 pub fn record_failed_attempt(profile_name: &str) -> Result<u32, String> {
     let mut state = RATE_LIMIT_STATE.lock().map_err(|e| format!("Lock error: {}", e))?;
     let entry = state.entry(profile_name.to_string()).or_insert_with(RateLimitEntry::default);
-    entry.failed_attempts += 1;
-    entry.last_attempt = Instant::now();
-    if entry.failed_attempts >= MAX_FAILED_ATTEMPTS {
-        entry.locked_until = Some(Instant::now() + Duration::from_secs(LOCKOUT_DURATION_SECS));
-        eprintln!("[SECURITY] Profile '{}' locked for {}s after {} failed attempts",
-            profile_name, LOCKOUT_DURATION_SECS, entry.failed_attempts);
-    }
+    register_failure(profile_name, entry);
+    persist_entry(profile_name, Some(entry));
     Ok(MAX_FAILED_ATTEMPTS.saturating_sub(entry.failed_attempts))
 }
 
@@ -125,20 +356,33 @@ pub fn record_successful_attempt(profile_name: &str) -> Result<(), String> {
     if let Some(entry) = state.get_mut(profile_name) {
         entry.failed_attempts = 0;
         entry.locked_until = None;
+        persist_entry(profile_name, None);
     }
     Ok(())
 }
 
+/// One more failed attempt; locks the profile once MAX_FAILED_ATTEMPTS is reached
+fn register_failure(profile_name: &str, entry: &mut RateLimitEntry) {
+    entry.failed_attempts += 1;
+    entry.last_attempt = SystemTime::now();
+    if entry.failed_attempts >= MAX_FAILED_ATTEMPTS {
+        entry.locked_until = Some(SystemTime::now() + Duration::from_secs(LOCKOUT_DURATION_SECS));
+        log::warn!("[SECURITY] Profile '{}' locked for {}s after {} failed attempts",
+            profile_name, LOCKOUT_DURATION_SECS, entry.failed_attempts);
+    }
+}
+
 fn calculate_delay(failed_attempts: u32) -> u64 {
     if failed_attempts == 0 { return 0; }
     let delay = BASE_DELAY_MS * 2u64.pow(failed_attempts.saturating_sub(1).min(20));
     delay.min(MAX_DELAY_MS)
 }
 
-/// Get the current failed attempt count for a profile.
-pub fn get_failed_attempts(profile_name: &str) -> u32 {
-    let state = RATE_LIMIT_STATE.lock().unwrap_or_else(|e| e.into_inner());
-    state.get(profile_name).map(|e| e.failed_attempts).unwrap_or(0)
+/// Forget all rate-limit state for a profile (e.g. once it is deleted).
+pub fn clear_rate_limit(profile_name: &str) -> bool {
+    let mut state = RATE_LIMIT_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    persist_entry(profile_name, None);
+    state.remove(profile_name).is_some()
 }
 
 /// Detect legacy SHA-256 hex hash (64 hex chars, no $argon2 prefix)
@@ -150,10 +394,22 @@ pub fn is_legacy_sha256_hash(stored_hash: &str) -> bool {
 
 /// Re-hash PIN from legacy SHA-256 to Argon2id
 pub fn migrate_pin_hash(raw_pin: &str) -> Result<String, String> {
-    eprintln!("[SECURITY] Migrating PIN hash from SHA-256 to Argon2id");
+    log::info!("[SECURITY] Migrating PIN hash from SHA-256 to Argon2id");
     hash_pin(raw_pin)
 }
 
+/// Derive a raw 32-byte key from a secret with the same Argon2id parameters as PIN hashing
+pub fn derive_key(secret: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, String> {
+    if salt.len() < 8 {
+        return Err("Salt too short".to_string());
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    get_argon2_hasher()
+        .hash_password_into(secret.as_bytes(), salt, &mut *key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,4 +438,86 @@ mod tests {
         assert!(is_legacy_sha256_hash("a665a45920422f9d417e4867efdc4fb8a04a1f3fff1fa07e998e86f7f7a27ae3"));
         assert!(!is_legacy_sha256_hash("$argon2id$v=19$m=65536,t=3,p=4$salt$hash"));
     }
+
+    #[test]
+    fn test_rate_limit_survives_restart() {
+        // Works on its own database and entries: the global limiter is shared by parallel tests
+        let path = std::env::temp_dir().join(format!("janus_rate_limit_{}.db", std::process::id()));
+        crate::init_db(&crate::open_db(&path).unwrap()).unwrap();
+
+        let (mut delay, mut lockout) = (RateLimitEntry::default(), RateLimitEntry::default());
+        for _ in 0..3 {
+            register_failure("restart_delay", &mut delay);
+        }
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            register_failure("restart_lockout", &mut lockout);
+        }
+        write_entry(&path, "restart_delay", Some(&delay)).unwrap();
+        write_entry(&path, "restart_lockout", Some(&lockout)).unwrap();
+        write_entry(&path, "restart_success", Some(&RateLimitEntry { failed_attempts: 1, ..Default::default() })).unwrap();
+        write_entry(&path, "restart_success", None).unwrap();
+
+        // Simulated relaunch: only the table remains
+        let now = SystemTime::now();
+        let loaded: HashMap<String, RateLimitEntry> = load_entries(&path).unwrap().into_iter().collect();
+        let delay = status_of(&loaded["restart_delay"], now);
+        assert_eq!(delay.failed_attempts, 3);
+        assert!(!delay.is_locked && delay.next_retry_secs > 0);
+        assert!(status_of(&loaded["restart_lockout"], now).is_locked);
+        assert!(!loaded.contains_key("restart_success"));
+
+        write_entry(&path, "restart_lockout", None).unwrap();
+        assert!(!load_entries(&path).unwrap().iter().any(|(name, _)| name == "restart_lockout"));
+
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_status_states() {
+        let now = SystemTime::now();
+        let clean = RateLimitEntry { failed_attempts: 0, last_attempt: now, locked_until: None };
+        assert_eq!(status_of(&clean, now), RateLimitStatus::default());
+
+        // 3 failures: 4 s cooldown from the last attempt
+        let cooling = RateLimitEntry { failed_attempts: 3, last_attempt: now - Duration::from_millis(1500), locked_until: None };
+        let status = status_of(&cooling, now);
+        assert_eq!((status.failed_attempts, status.is_locked, status.locked_until_secs, status.next_retry_secs), (3, false, None, 3));
+        assert_eq!(status_of(&cooling, now + Duration::from_secs(3)).next_retry_secs, 0);
+
+        let until = now + Duration::from_secs(600);
+        let locked = RateLimitEntry { failed_attempts: MAX_FAILED_ATTEMPTS, last_attempt: now, locked_until: Some(until) };
+        let status = status_of(&locked, now);
+        assert!(status.is_locked);
+        assert_eq!(status.next_retry_secs, 600);
+        assert_eq!(status.locked_until_secs, Some(until.duration_since(UNIX_EPOCH).unwrap().as_secs()));
+        assert_eq!(status_of(&locked, until + Duration::from_secs(1)), RateLimitStatus::default());
+
+        assert_eq!(get_rate_limit_status("status_never_seen"), RateLimitStatus::default());
+    }
+
+    #[test]
+    fn test_credential_policy() {
+        let strict = CredentialPolicy {
+            password_min_length: 12,
+            password_require_mixed_case: true,
+            password_require_digit: true,
+            password_require_symbol: true,
+            ..Default::default()
+        };
+        assert!(validate_policy(CredentialKind::Password, "correcthorse", &CredentialPolicy::default()).is_ok());
+        assert!(validate_policy(CredentialKind::Password, "short", &CredentialPolicy::default()).unwrap_err().contains("8"));
+        assert!(validate_policy(CredentialKind::Password, "correcthorse", &strict).unwrap_err().contains("majuscules"));
+        assert!(validate_policy(CredentialKind::Password, "Correct-Horse-9", &strict).is_ok());
+        // Password-only rules do not apply to PINs
+        assert!(validate_policy(CredentialKind::Pin, "1234", &strict).is_ok());
+
+        let result = evaluate_credential(CredentialKind::Password, "Correcthorse", &strict);
+        let failed: Vec<&str> = result.rules.iter().filter(|r| !r.passed).map(|r| r.rule).collect();
+        assert_eq!(failed, ["digit", "symbol"]);
+        assert!(!result.passed);
+        assert!((result.entropy_bits - 52f64.log2() * 12.0).abs() < 1e-9);
+        assert_eq!(estimate_entropy(""), 0.0);
+    }
 }
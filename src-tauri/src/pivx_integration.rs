@@ -1,6 +1,28 @@
 // pivx_integration.rs - Intégration PIVX
+// Supports: pivxd JSON-RPC (getblockcount, getaddressbalance/getaddressdeltas, which
+// need -addressindex) with basic auth, or the public blockbook explorer when no node
+// is configured.
+//
+// Balances are split in three parts:
+// - transparent: regular UTXOs of a D... address
+// - cold staking: P2CS delegations, listed by listcoldutxos on the node's wallet. The
+//   owner (D..., keeps spending rights) and the staker (S..., only stakes) both see them;
+//   an S... address holds nothing else.
+// - shield (Sapling, successor of zPIV): private, entered manually like Monero.
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::input_validation::PivxAddressKind;
+use crate::http::SendRetry;
+use crate::{http_client, input_validation, log_address, log_balance};
+
+/// 1 PIV = 10^8 satoshis
+const SATS_PER_PIV: f64 = 100_000_000.0;
+
+/// Explorateur blockbook utilisé sans nœud RPC
+const PIVX_EXPLORER_URL: &str = "https://explorer.pivx.link";
+
 // Structures pour PIVX
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PivxNodeInfo {
@@ -8,62 +30,362 @@ pub struct PivxNodeInfo {
     pub block_height: u64,
 }
 
+/// Message affiché à la place de la balance shield
+const SHIELD_MANUAL_ENTRY: &str = "saisie manuelle requise";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PivxBalance {
-    pub regular_balance: f64,
-    pub zpiv_balance: f64,
+    pub transparent_balance: f64,
+    /// Délégations cold staking de l'adresse (propriétaire ou staker); None sans identifiants RPC
+    pub cold_staking_balance: Option<f64>,
+    /// Toujours None: les balances shield ne sont pas lisibles depuis les données publiques
+    pub shield_balance: Option<f64>,
+    pub shield_status: String,
+    /// transparent + cold staking (la part shield est saisie à part)
     pub total_balance: f64,
+    /// "rpc" ou "explorer"
+    pub source: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PivxTransaction {
     pub txid: String,
+    /// Variation nette pour l'adresse (négative = sortie)
     pub amount: f64,
     pub confirmations: u32,
     pub time: u64,
 }
 
+fn sats_to_piv(sats: i64) -> f64 {
+    sats as f64 / SATS_PER_PIV
+}
+
+//
+// CLIENT JSON-RPC (pivxd)
+//
+
+struct PivxRpcClient {
+    client: reqwest::Client,
+    url: String,
+    credentials: Option<(String, String)>,
+}
+
+impl PivxRpcClient {
+    fn new(url: &str, rpc_user: Option<String>, rpc_password: Option<String>) -> Result<Self, String> {
+        input_validation::validate_node_url(url)?;
+        let credentials = match (rpc_user.filter(|u| !u.is_empty()), rpc_password) {
+            (Some(user), password) => Some((user, password.unwrap_or_default())),
+            (None, _) => None,
+        };
+        Ok(Self { client: http_client()?, url: url.trim().to_string(), credentials })
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String> {
+        let mut request = self.client.post(&self.url).json(&serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": "janus",
+            "method": method,
+            "params": params,
+        }));
+        if let Some((user, password)) = &self.credentials {
+            request = request.basic_auth(user, Some(password));
+        }
+        let response = request.send().await
+            .map_err(|e| format!("Nœud PIVX injoignable ({}): {}", self.url, e))?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(format!("{}: identifiants RPC refusés", method));
+        }
+        // pivxd répond 500 avec un corps JSON-RPC pour les erreurs de méthode
+        let body: serde_json::Value = response.json().await
+            .map_err(|e| format!("{}: réponse invalide: {}", method, e))?;
+        if let Some(error) = body.get("error").filter(|e| !e.is_null()) {
+            let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("erreur inconnue");
+            return Err(format!("{}: {}", method, message));
+        }
+        body.get("result").cloned().ok_or_else(|| format!("{}: réponse sans résultat", method))
+    }
+
+    async fn block_count(&self) -> Result<u64, String> {
+        self.call("getblockcount", serde_json::json!([])).await?
+            .as_u64()
+            .ok_or_else(|| "getblockcount: hauteur absente".to_string())
+    }
+}
+
+/// Les méthodes getaddress* n'existent que sur un nœud lancé avec -addressindex
+fn address_index_hint(error: String) -> String {
+    if error.contains("Method not found") || error.contains("Address index not enabled") {
+        format!("{} — le nœud doit être lancé avec -addressindex=1 (ou laissez le nœud vide pour passer par l'explorateur)", error)
+    } else {
+        error
+    }
+}
+
+async fn rpc_balance(rpc: &PivxRpcClient, address: &str) -> Result<f64, String> {
+    let result = rpc.call("getaddressbalance", serde_json::json!([{ "addresses": [address] }])).await
+        .map_err(address_index_hint)?;
+    Ok(sats_to_piv(result.get("balance").and_then(|b| b.as_i64()).unwrap_or(0)))
+}
+
+/// getaddressdeltas regroupés par transaction, les plus récentes d'abord
+async fn rpc_transactions(rpc: &PivxRpcClient, address: &str, limit: usize) -> Result<Vec<PivxTransaction>, String> {
+    let deltas = rpc.call("getaddressdeltas", serde_json::json!([{ "addresses": [address] }])).await
+        .map_err(address_index_hint)?;
+    let tip = rpc.block_count().await?;
+    let mut txs = group_address_deltas(deltas.as_array().map(Vec::as_slice).unwrap_or_default(), tip);
+    txs.truncate(limit);
+
+    // Horodatage des blocs concernés (un getblockheader par hauteur)
+    let mut block_times: HashMap<u64, u64> = HashMap::new();
+    for tx in &mut txs {
+        let height = (tip + 1).saturating_sub(tx.confirmations as u64);
+        if tx.confirmations == 0 {
+            continue;
+        }
+        if let Some(time) = block_times.get(&height) {
+            tx.time = *time;
+            continue;
+        }
+        let hash = rpc.call("getblockhash", serde_json::json!([height])).await?;
+        let header = rpc.call("getblockheader", serde_json::json!([hash])).await?;
+        let time = header.get("time").and_then(|t| t.as_u64()).unwrap_or(0);
+        block_times.insert(height, time);
+        tx.time = time;
+    }
+    Ok(txs)
+}
+
+/// Somme des deltas (satoshis) par txid; confirmations calculées sur la hauteur `tip`
+fn group_address_deltas(deltas: &[serde_json::Value], tip: u64) -> Vec<PivxTransaction> {
+    let mut by_tx: Vec<(String, i64, u64)> = Vec::new();
+    for delta in deltas {
+        let Some(txid) = delta.get("txid").and_then(|t| t.as_str()) else { continue };
+        let sats = delta.get("satoshis").and_then(|s| s.as_i64()).unwrap_or(0);
+        let height = delta.get("height").and_then(|h| h.as_u64()).unwrap_or(0);
+        match by_tx.iter_mut().find(|(id, _, _)| id == txid) {
+            Some(entry) => entry.1 += sats,
+            None => by_tx.push((txid.to_string(), sats, height)),
+        }
+    }
+    by_tx.sort_by_key(|(_, _, height)| std::cmp::Reverse(*height));
+    by_tx.into_iter()
+        .map(|(txid, sats, height)| PivxTransaction {
+            txid,
+            amount: sats_to_piv(sats),
+            confirmations: if height == 0 || height > tip { 0 } else { (tip - height + 1).min(u32::MAX as u64) as u32 },
+            time: 0,
+        })
+        .collect()
+}
+
+/// Somme (PIV) des UTXO cold staking dont `address` est le propriétaire ou le staker
+fn cold_staking_total(utxos: &[serde_json::Value], address: &str) -> f64 {
+    utxos.iter()
+        .filter(|u| ["coin-owner", "cold-staker"].iter().any(|k| u.get(*k).and_then(|a| a.as_str()) == Some(address)))
+        .map(|u| u.get("amount").and_then(|a| a.as_f64()).unwrap_or(0.0))
+        .sum()
+}
+
+/// listcoldutxos du wallet du nœud (délégations reçues en staker et, avec `true`,
+/// celles non whitelistées, ce qui couvre le côté propriétaire)
+async fn rpc_cold_staking(rpc: &PivxRpcClient, address: &str) -> Result<f64, String> {
+    let utxos = rpc.call("listcoldutxos", serde_json::json!([true])).await?;
+    Ok(cold_staking_total(utxos.as_array().map(Vec::as_slice).unwrap_or_default(), address))
+}
+
+//
+// EXPLORATEUR BLOCKBOOK
+//
+
+async fn explorer_address(address: &str, page_size: usize) -> Result<serde_json::Value, String> {
+    let url = format!("{}/api/v2/address/{}?details=txs&pageSize={}", PIVX_EXPLORER_URL, address, page_size.max(1));
+    let response = http_client()?
+        .get(&url)
+        .send_retry().await
+        .map_err(|e| format!("Explorateur PIVX injoignable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Explorateur PIVX: HTTP {}", response.status()));
+    }
+    response.json().await.map_err(|e| format!("Explorateur PIVX: réponse invalide: {}", e))
+}
+
+/// Les montants blockbook sont des chaînes en satoshis
+fn blockbook_sats(value: Option<&serde_json::Value>) -> i64 {
+    match value {
+        Some(serde_json::Value::String(s)) => s.parse().unwrap_or(0),
+        Some(v) => v.as_i64().unwrap_or(0),
+        None => 0,
+    }
+}
+
+/// Variation nette d'une transaction blockbook pour `address` (sorties reçues - entrées dépensées)
+fn blockbook_tx(tx: &serde_json::Value, address: &str) -> PivxTransaction {
+    let sum = |side: &str| -> i64 {
+        tx.get(side).and_then(|v| v.as_array()).map(|items| items.iter()
+            .filter(|item| item.get("addresses").and_then(|a| a.as_array())
+                .is_some_and(|a| a.iter().any(|x| x.as_str() == Some(address))))
+            .map(|item| blockbook_sats(item.get("value")))
+            .sum()).unwrap_or(0)
+    };
+    PivxTransaction {
+        txid: tx.get("txid").and_then(|t| t.as_str()).unwrap_or("").to_string(),
+        amount: sats_to_piv(sum("vout") - sum("vin")),
+        confirmations: tx.get("confirmations").and_then(|c| c.as_u64()).unwrap_or(0).min(u32::MAX as u64) as u32,
+        time: tx.get("blockTime").and_then(|t| t.as_u64()).unwrap_or(0),
+    }
+}
+
+//
+// COMMANDES TAURI
+//
+
+fn validated_address(address: &str) -> Result<(String, PivxAddressKind), String> {
+    let address = address.trim();
+    match input_validation::pivx_address_kind(address)? {
+        PivxAddressKind::Shield => Err(format!("PIVX shield: {} (adresse privée)", SHIELD_MANUAL_ENTRY)),
+        kind => Ok((address.to_string(), kind)),
+    }
+}
+
+fn configured_node(rpc_node: Option<String>) -> Option<String> {
+    rpc_node.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+}
+
 #[tauri::command]
-pub async fn test_pivx_node(rpc_node: String) -> Result<PivxNodeInfo, String> {
-    Ok(PivxNodeInfo { 
-        url: rpc_node, 
-        block_height: 12345 
-    })
+pub async fn test_pivx_node(
+    rpc_node: String,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
+) -> Result<PivxNodeInfo, String> {
+    let rpc = PivxRpcClient::new(&rpc_node, rpc_user, rpc_password)?;
+    let block_height = rpc.block_count().await?;
+    Ok(PivxNodeInfo { url: rpc.url, block_height })
 }
 
+/// Balance de l'adresse: transparente via le nœud (`rpc_node`, -addressindex requis) ou,
+/// sans nœud, via l'explorateur blockbook; cold staking via listcoldutxos quand des
+/// identifiants RPC sont fournis. Une erreur du nœud n'est jamais masquée.
 #[tauri::command]
 pub async fn get_pivx_balance(
-    _address: String,
-    _rpc_node: String,
-    _rpc_user: Option<String>,
-    _rpc_password: Option<String>,
+    address: String,
+    rpc_node: Option<String>,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
 ) -> Result<PivxBalance, String> {
+    let (address, kind) = validated_address(&address)?;
+    log_address("PIVX", &address);
+    let has_credentials = rpc_user.as_deref().is_some_and(|u| !u.is_empty());
+    let (transparent_balance, cold_staking_balance, source) = match configured_node(rpc_node) {
+        Some(node) => {
+            let rpc = PivxRpcClient::new(&node, rpc_user, rpc_password)?;
+            // Une adresse staker ne porte que des délégations
+            let transparent = match kind {
+                PivxAddressKind::ColdStaker => 0.0,
+                _ => rpc_balance(&rpc, &address).await?,
+            };
+            let cold = if has_credentials { Some(rpc_cold_staking(&rpc, &address).await?) } else { None };
+            (transparent, cold, "rpc")
+        }
+        None if kind == PivxAddressKind::ColdStaker => {
+            return Err("Adresse cold staking (S...): un nœud PIVX avec identifiants RPC est requis".to_string());
+        }
+        None => {
+            let data = explorer_address(&address, 1).await?;
+            (sats_to_piv(blockbook_sats(data.get("balance"))), None, "explorer")
+        }
+    };
+    let total_balance = transparent_balance + cold_staking_balance.unwrap_or(0.0);
+    log_balance("PIVX", total_balance);
     Ok(PivxBalance {
-        regular_balance: 10.5,
-        zpiv_balance: 5.2,
-        total_balance: 15.7,
+        transparent_balance,
+        cold_staking_balance,
+        shield_balance: None,
+        shield_status: SHIELD_MANUAL_ENTRY.to_string(),
+        total_balance,
+        source: source.to_string(),
     })
 }
 
 #[tauri::command]
 pub async fn get_pivx_transactions(
-    _address: String,
-    _rpc_node: String,
-    _rpc_user: Option<String>,
-    _rpc_password: Option<String>,
+    address: String,
+    rpc_node: Option<String>,
+    rpc_user: Option<String>,
+    rpc_password: Option<String>,
+    limit: Option<usize>,
 ) -> Result<Vec<PivxTransaction>, String> {
-    Ok(vec![
-        PivxTransaction {
-            txid: "tx123".to_string(),
-            amount: 2.5,
-            confirmations: 6,
-            time: 1234567890,
-        },
-        PivxTransaction {
-            txid: "tx456".to_string(),
-            amount: 3.7,
-            confirmations: 12,
-            time: 1234567891,
+    let (address, _) = validated_address(&address)?;
+    let limit = limit.unwrap_or(20).min(1000);
+    match configured_node(rpc_node) {
+        Some(node) => {
+            let rpc = PivxRpcClient::new(&node, rpc_user, rpc_password)?;
+            rpc_transactions(&rpc, &address, limit).await
+        }
+        None => {
+            let data = explorer_address(&address, limit).await?;
+            Ok(data.get("transactions").and_then(|t| t.as_array())
+                .map(|txs| txs.iter().map(|tx| blockbook_tx(tx, &address)).take(limit).collect())
+                .unwrap_or_default())
         }
-    ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "DLabsktzGMnsK5K9uRTMCF6NoYNY6ET4Bb";
+
+    #[test]
+    fn test_group_address_deltas() {
+        let deltas = serde_json::json!([
+            { "txid": "aa", "satoshis": 150_000_000, "height": 4_000_000 },
+            { "txid": "bb", "satoshis": -50_000_000, "height": 4_000_010 },
+            { "txid": "bb", "satoshis": 20_000_000, "height": 4_000_010 },
+        ]);
+        let txs = group_address_deltas(deltas.as_array().unwrap(), 4_000_010);
+        assert_eq!(txs.len(), 2);
+        assert_eq!((txs[0].txid.as_str(), txs[0].amount, txs[0].confirmations), ("bb", -0.3, 1));
+        assert_eq!((txs[1].txid.as_str(), txs[1].amount, txs[1].confirmations), ("aa", 1.5, 11));
+    }
+
+    #[test]
+    fn test_blockbook_tx_net_amount() {
+        let tx = serde_json::json!({
+            "txid": "cc", "confirmations": 3, "blockTime": 1_760_000_000,
+            "vin": [{ "addresses": [ADDRESS], "value": "500000000" }],
+            "vout": [
+                { "addresses": ["DOther"], "value": "200000000" },
+                { "addresses": [ADDRESS], "value": "290000000" }
+            ]
+        });
+        let parsed = blockbook_tx(&tx, ADDRESS);
+        assert_eq!(parsed.amount, -2.1);
+        assert_eq!((parsed.confirmations, parsed.time), (3, 1_760_000_000));
+        assert_eq!(blockbook_sats(Some(&serde_json::json!("123"))), 123);
+    }
+
+    #[test]
+    fn test_cold_staking_owner_and_staker() {
+        let utxos = serde_json::json!([
+            { "txid": "aa", "amount": 1000.0, "coin-owner": ADDRESS, "cold-staker": "SStaker" },
+            { "txid": "bb", "amount": 250.5, "coin-owner": "DOther", "cold-staker": "SStaker" },
+        ]);
+        let utxos = utxos.as_array().unwrap();
+        assert_eq!(cold_staking_total(utxos, ADDRESS), 1000.0);
+        assert_eq!(cold_staking_total(utxos, "SStaker"), 1250.5);
+        assert_eq!(cold_staking_total(utxos, "DNobody"), 0.0);
+    }
+
+    #[test]
+    fn test_pivx_address_validation() {
+        assert_eq!(validated_address(ADDRESS).unwrap().1, PivxAddressKind::Transparent);
+        assert_eq!(validated_address(&format!("S{}", &ADDRESS[1..])).unwrap().1, PivxAddressKind::ColdStaker);
+        let shield = format!("ps1{}", "q".repeat(75));
+        assert_eq!(input_validation::pivx_address_kind(&shield), Ok(PivxAddressKind::Shield));
+        assert!(validated_address(&shield).unwrap_err().contains(SHIELD_MANUAL_ENTRY));
+        assert!(validated_address("DLabsktzGMnsK5K9uRTMCF6NoYNY6ET4B0").is_err());
+        assert!(validated_address("XLabsktzGMnsK5K9uRTMCF6NoYNY6ET4Bb").is_err());
+        assert!(configured_node(Some("  ".into())).is_none());
+    }
 }
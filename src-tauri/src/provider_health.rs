@@ -0,0 +1,591 @@
+use crate::http::FetchError;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+//
+// FOURNISSEURS LIMITÉS (BLOCKCHAIR / ETHERSCAN)
+//
+
+/// Free tiers: Blockchair ~30 req/min, Etherscan 5 req/s
+pub const DEFAULT_BLOCKCHAIR_RPM: u32 = 30;
+pub const DEFAULT_ETHERSCAN_RPM: u32 = 240;
+const RPM_RANGE: std::ops::RangeInclusive<u32> = 1..=600;
+/// Identical requests within this window are answered from memory
+const CACHE_TTL: Duration = Duration::from_secs(90);
+const CACHE_MAX_ENTRIES: usize = 512;
+
+/// Rate-limited providers, recognized by API host
+const TRACKED_HOSTS: &[(&str, &str)] = &[
+    ("api.blockchair.com", "blockchair"),
+    ("api.etherscan.io", "etherscan"),
+];
+
+pub fn provider_of(url: &str) -> Option<&'static str> {
+    let host = reqwest::Url::parse(url).ok()?.host_str()?.to_string();
+    TRACKED_HOSTS.iter().find(|(h, _)| *h == host).map(|(_, name)| *name)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub requests_per_minute: u32,
+    /// Requests that reached the provider
+    pub requests: u64,
+    pub cache_hits: u64,
+    /// Requests delayed by the local rate limiter
+    pub throttled: u64,
+    /// HTTP 429 answers despite the limiter
+    pub rate_limited: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<i64>,
+    /// Per-key usage when several keys rotate (Etherscan)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<KeyUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct KeyUsage {
+    pub key_hint: String,
+    pub requests: u64,
+    /// "Max rate limit reached" answers
+    pub rate_limited: u64,
+    /// Seconds left on the bench, 0 when the key is in rotation
+    pub benched_secs: u64,
+}
+
+/// Token bucket refilled continuously; a request that finds it empty reserves the
+/// next token and waits for it, so bursts queue up instead of being dropped.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    per_sec: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(requests_per_minute: u32, now: Instant) -> Self {
+        let capacity = requests_per_minute.max(1) as f64;
+        Self { capacity, tokens: capacity, per_sec: capacity / 60.0, updated: now }
+    }
+
+    /// Takes a token and returns how long to wait before using it
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.updated = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_sec)
+        }
+    }
+}
+
+struct ProviderState {
+    bucket: TokenBucket,
+    health: ProviderHealth,
+}
+
+static PROVIDERS: Lazy<Mutex<HashMap<&'static str, ProviderState>>> = Lazy::new(|| {
+    let now = Instant::now();
+    let state = |name: &str, rpm: u32| ProviderState {
+        bucket: TokenBucket::new(rpm, now),
+        health: ProviderHealth { provider: name.to_string(), requests_per_minute: rpm, ..Default::default() },
+    };
+    Mutex::new(HashMap::from([
+        ("blockchair", state("blockchair", DEFAULT_BLOCKCHAIR_RPM)),
+        ("etherscan", state("etherscan", DEFAULT_ETHERSCAN_RPM)),
+    ]))
+});
+
+/// URL (query included) → decoded body. Memory only.
+static CACHE: Lazy<Mutex<HashMap<String, (Instant, serde_json::Value)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn parse_requests_per_minute(value: &str) -> Result<u32, String> {
+    value.trim().parse::<u32>().ok()
+        .filter(|rpm| RPM_RANGE.contains(rpm))
+        .ok_or_else(|| format!("Limite invalide : {} à {} requêtes par minute", RPM_RANGE.start(), RPM_RANGE.end()))
+}
+
+/// `blockchair_requests_per_minute` / `etherscan_requests_per_minute` / `api_hourly_budget`;
+/// invalid values fall back to the defaults
+pub fn configure(blockchair_rpm: Option<&str>, etherscan_rpm: Option<&str>, hourly_budget: Option<&str>) {
+    HOURLY_BUDGET.store(hourly_budget.and_then(|v| parse_hourly_budget(v).ok()).unwrap_or(0), Ordering::Relaxed);
+    let Ok(mut providers) = PROVIDERS.lock() else { return };
+    let now = Instant::now();
+    for (name, value, default) in [
+        ("blockchair", blockchair_rpm, DEFAULT_BLOCKCHAIR_RPM),
+        ("etherscan", etherscan_rpm, DEFAULT_ETHERSCAN_RPM),
+    ] {
+        let rpm = value.and_then(|v| parse_requests_per_minute(v).ok()).unwrap_or(default);
+        if let Some(state) = providers.get_mut(name).filter(|s| s.health.requests_per_minute != rpm) {
+            state.bucket = TokenBucket::new(rpm, now);
+            state.health.requests_per_minute = rpm;
+        }
+    }
+}
+
+fn record(provider: &str, update: impl FnOnce(&mut ProviderHealth)) {
+    if let Some(state) = PROVIDERS.lock().ok().as_mut().and_then(|p| p.get_mut(provider)) {
+        update(&mut state.health);
+    }
+}
+
+fn cached(url: &str) -> Option<serde_json::Value> {
+    let cache = CACHE.lock().ok()?;
+    let (at, body) = cache.get(url)?;
+    (at.elapsed() < CACHE_TTL).then(|| body.clone())
+}
+
+fn store(url: &str, body: &serde_json::Value) {
+    let Ok(mut cache) = CACHE.lock() else { return };
+    if cache.len() >= CACHE_MAX_ENTRIES {
+        cache.retain(|_, (at, _)| at.elapsed() < CACHE_TTL);
+    }
+    if cache.len() < CACHE_MAX_ENTRIES {
+        cache.insert(url.to_string(), (Instant::now(), body.clone()));
+    }
+}
+
+//
+// ROTATION DES CLÉS ETHERSCAN
+//
+
+/// A key that answered "Max rate limit reached" sits out this long
+const KEY_BENCH: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+struct KeyState {
+    requests: u64,
+    rate_limited: u64,
+    last_used: Option<Instant>,
+    benched_until: Option<Instant>,
+}
+
+/// Usage since startup of every Etherscan key seen, by key
+static ETHERSCAN_KEYS: Lazy<Mutex<HashMap<String, KeyState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Keys of the `etherscan_api_key` setting: comma, space or newline separated, in order, without duplicates
+pub fn parse_keys(value: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for key in value.split(|c: char| c == ',' || c.is_whitespace()).filter(|k| !k.is_empty()) {
+        if !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// Key for the next Etherscan request: the least recently used one off the bench (all benched:
+/// the one back soonest). Empty when none is configured.
+pub fn next_etherscan_key(configured: &str) -> String {
+    let keys = parse_keys(configured);
+    if keys.len() < 2 {
+        return keys.into_iter().next().unwrap_or_default();
+    }
+    let Ok(mut states) = ETHERSCAN_KEYS.lock() else { return keys[0].clone() };
+    let now = Instant::now();
+    let pick = keys.iter()
+        .min_by_key(|key| {
+            let state = states.get(key.as_str());
+            let benched = state.and_then(|s| s.benched_until).filter(|until| *until > now);
+            (benched, state.and_then(|s| s.last_used))
+        })
+        .cloned()
+        .unwrap_or_default();
+    states.entry(pick.clone()).or_default().last_used = Some(now);
+    pick
+}
+
+/// `text` with every configured key masked (error texts may quote the URL, and with it the key)
+pub fn mask_keys(text: &str, configured: &str) -> String {
+    parse_keys(configured).iter().fold(text.to_string(), |text, key| text.replace(key.as_str(), "***"))
+}
+
+fn record_key(key: &str, update: impl FnOnce(&mut KeyState)) {
+    if let Ok(mut states) = ETHERSCAN_KEYS.lock() {
+        update(states.entry(key.to_string()).or_default());
+    }
+}
+
+/// `apikey` query parameter of an Etherscan URL
+fn api_key_of(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    url.query_pairs().find(|(k, _)| k == "apikey").map(|(_, v)| v.into_owned()).filter(|k| !k.is_empty())
+}
+
+/// Cache key without the API key, so that rotating keys still share cached answers
+fn cache_key(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else { return url.to_string() };
+    let pairs: Vec<(String, String)> = parsed.query_pairs()
+        .filter(|(k, _)| k != "apikey")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.to_string()
+}
+
+/// Etherscan answers its rate limit with HTTP 200 and this payload
+fn is_rate_limit_payload(body: &serde_json::Value) -> bool {
+    body.get("status").and_then(|s| s.as_str()) == Some("0")
+        && body.get("result").and_then(|r| r.as_str()).is_some_and(|r| r.to_lowercase().contains("rate limit"))
+}
+
+fn key_hint(key: &str) -> String {
+    match key.get(..4).zip(key.get(key.len().saturating_sub(4)..)) {
+        Some((head, tail)) if key.len() > 8 => format!("{}…{}", head, tail),
+        _ => "…".to_string(),
+    }
+}
+
+fn key_usage() -> Vec<KeyUsage> {
+    let Ok(states) = ETHERSCAN_KEYS.lock() else { return Vec::new() };
+    let now = Instant::now();
+    let mut usage: Vec<KeyUsage> = states.iter()
+        .map(|(key, s)| KeyUsage {
+            key_hint: key_hint(key),
+            requests: s.requests,
+            rate_limited: s.rate_limited,
+            benched_secs: s.benched_until.map(|until| until.saturating_duration_since(now).as_secs()).unwrap_or(0),
+        })
+        .collect();
+    usage.sort_by(|a, b| a.key_hint.cmp(&b.key_hint));
+    usage
+}
+
+/// GET + JSON for the Blockchair / Etherscan helpers: served from the TTL cache unless
+/// `force_refresh`, otherwise paced by the provider's token bucket. Other hosts go
+/// straight through (shared client, retry).
+pub async fn get_json(client: &reqwest::Client, url: &str, force_refresh: bool) -> Result<serde_json::Value, String> {
+    let Some(provider) = provider_of(url) else {
+        return crate::http::get_json(client, url).await;
+    };
+    let api_key = api_key_of(url).filter(|_| provider == "etherscan");
+    let cache_url = cache_key(url);
+    if !force_refresh {
+        if let Some(body) = cached(&cache_url) {
+            record(provider, |h| h.cache_hits += 1);
+            return Ok(body);
+        }
+    }
+
+    let wait = PROVIDERS.lock().ok()
+        .and_then(|mut p| p.get_mut(provider).map(|s| s.bucket.reserve(Instant::now())))
+        .unwrap_or(Duration::ZERO);
+    if !wait.is_zero() {
+        record(provider, |h| h.throttled += 1);
+        log::debug!("[PROVIDER] {} throttled for {} ms", provider, wait.as_millis());
+        tokio::time::sleep(wait).await;
+        // An identical request queued ahead of us may have filled the cache meanwhile
+        if let Some(body) = cached(&cache_url).filter(|_| !force_refresh) {
+            record(provider, |h| h.cache_hits += 1);
+            return Ok(body);
+        }
+    }
+
+    record(provider, |h| h.requests += 1);
+    if let Some(key) = &api_key {
+        record_key(key, |k| k.requests += 1);
+    }
+    let result = match crate::http::fetch_json(client.get(url)).await {
+        Err(FetchError::Status(reqwest::StatusCode::TOO_MANY_REQUESTS)) => {
+            record(provider, |h| h.rate_limited += 1);
+            Err(format!("{}: HTTP 429 (limite de requêtes atteinte)", provider))
+        }
+        // Bench the key so that the next requests rotate to the others; never cached
+        Ok(body) if is_rate_limit_payload(&body) => {
+            record(provider, |h| h.rate_limited += 1);
+            if let Some(key) = &api_key {
+                record_key(key, |k| {
+                    k.rate_limited += 1;
+                    k.benched_until = Some(Instant::now() + KEY_BENCH);
+                });
+                log::warn!("[PROVIDER] {} key {} rate limited, benched for {}s", provider, key_hint(key), KEY_BENCH.as_secs());
+            }
+            Err(format!("{}: HTTP 429 (limite de requêtes atteinte)", provider))
+        }
+        result => result.map_err(|e| format!("{}: {}", provider, e)),
+    };
+    match &result {
+        Ok(body) => {
+            store(&cache_url, body);
+            record(provider, |h| h.last_success_at = Some(chrono::Utc::now().timestamp()));
+        }
+        Err(e) => record(provider, |h| {
+            h.errors += 1;
+            h.last_error = Some(e.clone());
+        }),
+    }
+    result
+}
+
+//
+// CONSOMMATION PAR FOURNISSEUR (24 H)
+//
+
+const USAGE_HOURS: usize = 24;
+/// Latency samples kept per provider and hour for the percentile; later requests are still counted
+const LATENCY_SAMPLES_PER_HOUR: usize = 512;
+const MAX_HOURLY_BUDGET: u32 = 1_000_000;
+
+/// Requests per provider and per hour past which `api-budget-exceeded` is emitted; 0 = off
+static HOURLY_BUDGET: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Default, Clone)]
+struct HourUsage {
+    /// Hours since the epoch; a slot holding an older hour is reset on reuse
+    hour: i64,
+    requests: u64,
+    errors: u64,
+    latencies_ms: Vec<u32>,
+    budget_warned: bool,
+}
+
+/// Ring buffer per provider: slot `hour % 24`
+static USAGE: Lazy<Mutex<HashMap<String, [HourUsage; USAGE_HOURS]>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ApiUsage {
+    pub provider: String,
+    /// Last 24 h
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub p95_latency_ms: Option<u32>,
+    pub current_hour_requests: u64,
+    /// Requests per hour, oldest first, the current hour last
+    pub hourly: Vec<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetWarning {
+    pub provider: String,
+    pub requests: u64,
+    pub budget: u32,
+}
+
+pub fn parse_hourly_budget(value: &str) -> Result<u32, String> {
+    value.trim().parse::<u32>().ok()
+        .filter(|n| *n <= MAX_HOURLY_BUDGET)
+        .ok_or_else(|| format!("Budget horaire invalide : 0 (désactivé) à {}", MAX_HOURLY_BUDGET))
+}
+
+fn current_hour() -> i64 {
+    chrono::Utc::now().timestamp().div_euclid(3600)
+}
+
+/// Tracked providers by name, other hosts by host name; local nodes are not counted
+fn usage_provider(url: &reqwest::Url) -> Option<String> {
+    let host = url.host_str()?;
+    if matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "::1") {
+        return None;
+    }
+    Some(TRACKED_HOSTS.iter().find(|(h, _)| *h == host).map(|(_, name)| name.to_string()).unwrap_or_else(|| host.to_string()))
+}
+
+fn hour_slot(slots: &mut [HourUsage; USAGE_HOURS], hour: i64) -> &mut HourUsage {
+    let slot = &mut slots[hour.rem_euclid(USAGE_HOURS as i64) as usize];
+    if slot.hour != hour {
+        *slot = HourUsage { hour, ..Default::default() };
+    }
+    slot
+}
+
+/// Called by the shared HTTP client for every attempt that went out
+pub fn record_usage(url: &reqwest::Url, latency: Duration, ok: bool) {
+    if let Some(provider) = usage_provider(url) {
+        record_usage_at(&provider, current_hour(), latency, ok);
+    }
+}
+
+fn record_usage_at(provider: &str, hour: i64, latency: Duration, ok: bool) {
+    let Ok(mut usage) = USAGE.lock() else { return };
+    let slot = hour_slot(usage.entry(provider.to_string()).or_default(), hour);
+    slot.requests += 1;
+    if !ok {
+        slot.errors += 1;
+    }
+    if slot.latencies_ms.len() < LATENCY_SAMPLES_PER_HOUR {
+        slot.latencies_ms.push(latency.as_millis().min(u32::MAX as u128) as u32);
+    }
+}
+
+/// Nearest-rank percentile
+fn percentile(values: &mut [u32], pct: f64) -> Option<u32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = ((pct / 100.0) * values.len() as f64).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
+
+fn usage_stats(hour: i64) -> Vec<ApiUsage> {
+    let Ok(usage) = USAGE.lock() else { return Vec::new() };
+    let mut stats: Vec<ApiUsage> = usage.iter()
+        .map(|(provider, slots)| {
+            let recent = |h: i64| slots.iter().find(|s| s.hour == h && s.requests > 0);
+            let hours = (hour - USAGE_HOURS as i64 + 1)..=hour;
+            let hourly: Vec<u64> = hours.clone().map(|h| recent(h).map_or(0, |s| s.requests)).collect();
+            let requests = hourly.iter().sum();
+            let errors = hours.clone().filter_map(recent).map(|s| s.errors).sum();
+            let mut latencies: Vec<u32> = hours.filter_map(recent).flat_map(|s| s.latencies_ms.iter().copied()).collect();
+            ApiUsage {
+                provider: provider.clone(),
+                requests,
+                errors,
+                error_rate: if requests > 0 { errors as f64 / requests as f64 } else { 0.0 },
+                p95_latency_ms: percentile(&mut latencies, 95.0),
+                current_hour_requests: *hourly.last().unwrap_or(&0),
+                hourly,
+            }
+        })
+        .filter(|s| s.requests > 0)
+        .collect();
+    stats.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.provider.cmp(&b.provider)));
+    stats
+}
+
+/// Providers past the hourly budget during the current hour, each reported once per hour
+pub fn budget_overruns() -> Vec<BudgetWarning> {
+    budget_overruns_at(current_hour(), HOURLY_BUDGET.load(Ordering::Relaxed))
+}
+
+fn budget_overruns_at(hour: i64, budget: u32) -> Vec<BudgetWarning> {
+    if budget == 0 {
+        return Vec::new();
+    }
+    let Ok(mut usage) = USAGE.lock() else { return Vec::new() };
+    let mut warnings: Vec<BudgetWarning> = usage.iter_mut()
+        .filter_map(|(provider, slots)| {
+            let slot = hour_slot(slots, hour);
+            if slot.requests <= budget as u64 || slot.budget_warned {
+                return None;
+            }
+            slot.budget_warned = true;
+            Some(BudgetWarning { provider: provider.clone(), requests: slot.requests, budget })
+        })
+        .collect();
+    warnings.sort_by(|a, b| a.provider.cmp(&b.provider));
+    warnings
+}
+
+/// Requests, error rate and p95 latency per provider over the last 24 h (memory only, reset at restart)
+#[tauri::command]
+pub fn get_api_usage_stats() -> Result<Vec<ApiUsage>, String> {
+    Ok(usage_stats(current_hour()))
+}
+
+#[tauri::command]
+pub fn get_provider_health() -> Result<Vec<ProviderHealth>, String> {
+    let providers = PROVIDERS.lock().map_err(|e| e.to_string())?;
+    let mut health: Vec<ProviderHealth> = providers.values().map(|s| s.health.clone()).collect();
+    if let Some(etherscan) = health.iter_mut().find(|h| h.provider == "etherscan") {
+        etherscan.keys = key_usage();
+    }
+    health.sort_by(|a, b| a.provider.cmp(&b.provider));
+    Ok(health)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_delays_instead_of_dropping() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(30, start);
+        for _ in 0..30 {
+            assert_eq!(bucket.reserve(start), Duration::ZERO);
+        }
+        // 30/min = one token every 2 s; queued callers wait in turn
+        assert_eq!(bucket.reserve(start), Duration::from_secs(2));
+        assert_eq!(bucket.reserve(start), Duration::from_secs(4));
+        // Refill never exceeds the capacity
+        let mut bucket = TokenBucket::new(30, start);
+        assert_eq!(bucket.reserve(start + Duration::from_secs(3600)), Duration::ZERO);
+        assert!(bucket.tokens <= 29.0);
+    }
+
+    #[test]
+    fn only_rate_limited_hosts_are_tracked() {
+        assert_eq!(provider_of("https://api.blockchair.com/bitcoin/stats"), Some("blockchair"));
+        assert_eq!(provider_of("https://api.etherscan.io/api?module=proxy&action=eth_blockNumber"), Some("etherscan"));
+        assert_eq!(provider_of("https://blockstream.info/api/blocks/tip/height"), None);
+        assert_eq!(provider_of("not a url"), None);
+        assert!(parse_requests_per_minute("0").is_err());
+        assert_eq!(parse_requests_per_minute("60"), Ok(60));
+    }
+
+    #[test]
+    fn etherscan_keys_rotate_and_skip_benched_ones() {
+        assert_eq!(parse_keys(" AAAA1111BBBB, CCCC2222DDDD\nAAAA1111BBBB "), vec!["AAAA1111BBBB", "CCCC2222DDDD"]);
+        assert_eq!(next_etherscan_key(""), "");
+        assert_eq!(next_etherscan_key("SOLOKEY00001"), "SOLOKEY00001");
+
+        // Keys unique to this test: the usage table is process-wide
+        let keys = "ROTATEKEY0001,ROTATEKEY0002,ROTATEKEY0003";
+        let picks: Vec<String> = (0..6).map(|_| next_etherscan_key(keys)).collect();
+        assert_eq!(picks[..3], ["ROTATEKEY0001", "ROTATEKEY0002", "ROTATEKEY0003"]);
+        assert_eq!(picks[3..], picks[..3]);
+
+        record_key("ROTATEKEY0001", |k| k.benched_until = Some(Instant::now() + KEY_BENCH));
+        for _ in 0..4 {
+            assert_ne!(next_etherscan_key(keys), "ROTATEKEY0001");
+        }
+
+        let url = "https://api.etherscan.io/v2/api?chainid=1&module=proxy&action=eth_blockNumber&apikey=ROTATEKEY0002";
+        assert_eq!(api_key_of(url).as_deref(), Some("ROTATEKEY0002"));
+        assert_eq!(cache_key(url), "https://api.etherscan.io/v2/api?chainid=1&module=proxy&action=eth_blockNumber");
+        assert!(is_rate_limit_payload(&serde_json::json!({ "status": "0", "message": "NOTOK", "result": "Max rate limit reached" })));
+        assert!(!is_rate_limit_payload(&serde_json::json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" })));
+        assert_eq!(key_hint("ROTATEKEY0002"), "ROTA…0002");
+    }
+
+    #[test]
+    fn usage_is_counted_per_provider_and_hour() {
+        let url = |u: &str| reqwest::Url::parse(u).unwrap();
+        assert_eq!(usage_provider(&url("https://api.blockchair.com/bitcoin/stats")).as_deref(), Some("blockchair"));
+        assert_eq!(usage_provider(&url("https://mempool.space/api/blocks/tip/height")).as_deref(), Some("mempool.space"));
+        assert_eq!(usage_provider(&url("http://127.0.0.1:18083/json_rpc")), None);
+        assert_eq!(percentile(&mut [], 95.0), None);
+        assert_eq!(percentile(&mut (1..=100).rev().collect::<Vec<u32>>(), 95.0), Some(95));
+        assert!(parse_hourly_budget("-1").is_err());
+
+        // Provider and hours unique to this test: the usage table is process-wide
+        let (provider, hour) = ("usage-test.example", 1_000_000);
+        for ms in 1..=20 {
+            record_usage_at(provider, hour - 30, Duration::from_millis(5000), false);
+            record_usage_at(provider, hour - 1, Duration::from_millis(ms * 10), ms % 10 != 0);
+        }
+        record_usage_at(provider, hour, Duration::from_millis(10), true);
+        let stats = usage_stats(hour).into_iter().find(|s| s.provider == provider).unwrap();
+        // The 30 h old requests are out of the window
+        assert_eq!((stats.requests, stats.errors, stats.current_hour_requests), (21, 2, 1));
+        assert_eq!(stats.p95_latency_ms, Some(190));
+        assert_eq!(stats.hourly.len(), USAGE_HOURS);
+        assert_eq!(stats.hourly[USAGE_HOURS - 2..], [20, 1]);
+
+        // Slot of hour - 1 reused 24 h later: starts from zero
+        record_usage_at(provider, hour + 23, Duration::from_millis(10), true);
+        let stats = usage_stats(hour + 23).into_iter().find(|s| s.provider == provider).unwrap();
+        assert_eq!((stats.requests, stats.hourly[0], stats.current_hour_requests), (2, 1, 1));
+
+        for _ in 0..3 {
+            record_usage_at(provider, hour + 23, Duration::from_millis(10), true);
+        }
+        let over = |budget| budget_overruns_at(hour + 23, budget).into_iter().filter(|w| w.provider == provider).count();
+        assert_eq!(over(0), 0);
+        assert_eq!(over(10), 0);
+        assert_eq!(over(3), 1);
+        // Once per hour
+        assert_eq!(over(3), 0);
+    }
+}
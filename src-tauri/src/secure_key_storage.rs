@@ -1,12 +1,20 @@
 // Secure Key Storage Module
 // Provides secure storage and management of cryptographic keys
+//
+// The master key lives in the OS keyring when one is available (Secret Service/KWallet,
+// macOS Keychain, Windows Credential Manager). Headless systems fall back to a 0600 file.
 
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
+use std::path::Path;
 use sodiumoxide::crypto::secretbox;
 use once_cell::sync::OnceCell;
 
-/// Secure key storage in a protected file
+const KEY_FILE: &str = "logging_key.bin";
+// Written once the key has moved to the keyring: never silently generate a new key after that
+const KEYRING_MARKER_FILE: &str = "logging_key.keyring";
+
+/// Secure key storage (OS keyring, or a protected file as fallback)
 pub struct SecureKeyStorage {
     key: secretbox::Key,
 }
@@ -20,45 +28,165 @@ impl SecureKeyStorage {
         std::fs::create_dir_all(&data_dir)
             .map_err(|e| format!("Failed to create security directory: {}", e))?;
 
-        let key_file_path = data_dir.join("logging_key.bin");
-
-        if key_file_path.exists() {
-            let mut file = File::open(&key_file_path)
-                .map_err(|e| format!("Failed to open key file: {}", e))?;
+        let key = load_master_key(&data_dir, os_keyring::load, os_keyring::store)?;
+        Ok(SecureKeyStorage { key })
+    }
 
-            let mut key_bytes = vec![0u8; secretbox::KEYBYTES];
-            file.read_exact(&mut key_bytes)
-                .map_err(|e| format!("Failed to read key: {}", e))?;
+    pub fn get_key(&self) -> secretbox::Key {
+        secretbox::Key::from_slice(self.key.as_ref()).unwrap()
+    }
+}
 
-            let key = secretbox::Key::from_slice(&key_bytes)
-                .ok_or_else(|| "Invalid key length".to_string())?;
+/// Master key from the keyring (`load`/`store`), or from the key file in `data_dir` as fallback
+pub(crate) fn load_master_key(
+    data_dir: &Path,
+    load: impl Fn() -> Result<Option<secretbox::Key>, String>,
+    store: impl Fn(&secretbox::Key) -> Result<(), String>,
+) -> Result<secretbox::Key, String> {
+    let key_file_path = data_dir.join(KEY_FILE);
+    let marker_path = data_dir.join(KEYRING_MARKER_FILE);
+
+    match load() {
+        Ok(Some(key)) => {
+            // Leftover of an interrupted migration: only wipe it if it holds the same key
+            if key_file_path.exists() && read_key_file(&key_file_path).ok().as_ref() == Some(&key) {
+                secure_wipe(&key_file_path)?;
+            }
+            Ok(key)
+        }
+        Ok(None) => {
+            let existing = key_file_path.exists();
+            if !existing && marker_path.exists() {
+                // The key was moved to the keyring, which no longer has it: a fresh key
+                // would make every encrypted TOTP secret and log unreadable
+                return Err("Clé maître introuvable dans le trousseau système alors qu'elle y a été déplacée".to_string());
+            }
+            let key = if existing { read_key_file(&key_file_path)? } else { secretbox::gen_key() };
+
+            // One-time migration: file -> keyring, checked by reading the entry back
+            if store(&key).is_ok() && matches!(load(), Ok(Some(ref k)) if *k == key) {
+                write_private_file(&marker_path, b"keyring")?;
+                if existing {
+                    secure_wipe(&key_file_path)?;
+                    log::info!("[SECURITY] Master key moved from {} to the OS keyring", KEY_FILE);
+                }
+                return Ok(key);
+            }
 
-            Ok(SecureKeyStorage { key })
-        } else {
+            if !existing {
+                write_private_file(&key_file_path, key.as_ref())?;
+            }
+            log::warn!("[SECURITY] OS keyring refused the master key, using {}", KEY_FILE);
+            Ok(key)
+        }
+        Err(e) => {
+            log::warn!("[SECURITY] OS keyring unavailable ({}), using {}", e, KEY_FILE);
+            if key_file_path.exists() {
+                return read_key_file(&key_file_path);
+            }
+            if marker_path.exists() {
+                // A fresh key would make every encrypted TOTP secret unreadable
+                return Err(format!("Trousseau système indisponible ({}) — la clé maître y est stockée", e));
+            }
             let key = secretbox::gen_key();
+            write_private_file(&key_file_path, key.as_ref())?;
+            Ok(key)
+        }
+    }
+}
 
-            let mut file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .open(&key_file_path)
-                .map_err(|e| format!("Failed to create key file: {}", e))?;
+fn read_key_file(path: &Path) -> Result<secretbox::Key, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open key file: {}", e))?;
 
-            file.write_all(key.as_ref())
-                .map_err(|e| format!("Failed to write key: {}", e))?;
+    let mut key_bytes = vec![0u8; secretbox::KEYBYTES];
+    file.read_exact(&mut key_bytes)
+        .map_err(|e| format!("Failed to read key: {}", e))?;
 
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let perms = std::fs::Permissions::from_mode(0o600);
-                std::fs::set_permissions(&key_file_path, perms).ok();
-            }
+    secretbox::Key::from_slice(&key_bytes)
+        .ok_or_else(|| "Invalid key length".to_string())
+}
 
-            Ok(SecureKeyStorage { key })
+fn write_private_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|e| format!("Failed to create key file: {}", e))?;
+
+    file.write_all(content)
+        .map_err(|e| format!("Failed to write key: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let perms = std::fs::Permissions::from_mode(0o600);
+        std::fs::set_permissions(path, perms).ok();
+    }
+    Ok(())
+}
+
+/// Overwrite a file with zeros, flush it to disk, then delete it.
+pub fn secure_wipe(path: &Path) -> Result<(), String> {
+    let len = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?
+        .len() as usize;
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(&vec![0u8; len])
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to wipe {}: {}", path.display(), e))?;
+    drop(file);
+    std::fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))
+}
+
+/// Master key carried by a restored full backup: stored in the OS keyring so the next
+/// start uses it (the restored security/ file covers systems without a keyring).
+pub fn adopt_master_key(key_bytes: &[u8]) -> Result<(), String> {
+    let key = secretbox::Key::from_slice(key_bytes).ok_or_else(|| "Invalid key length".to_string())?;
+    os_keyring::store(&key)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+mod os_keyring {
+    use sodiumoxide::crypto::secretbox;
+
+    const SERVICE: &str = "janus-monitor";
+    const ACCOUNT: &str = "master-key";
+
+    fn entry() -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, ACCOUNT).map_err(|e| e.to_string())
+    }
+
+    /// Ok(None) when the keyring works but holds no key yet
+    pub fn load() -> Result<Option<secretbox::Key>, String> {
+        match entry()?.get_secret() {
+            Ok(bytes) => secretbox::Key::from_slice(&bytes)
+                .map(Some)
+                .ok_or_else(|| "Invalid key length in keyring".to_string()),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
         }
     }
 
-    pub fn get_key(&self) -> secretbox::Key {
-        secretbox::Key::from_slice(self.key.as_ref()).unwrap()
+    pub fn store(key: &secretbox::Key) -> Result<(), String> {
+        entry()?.set_secret(key.as_ref()).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod os_keyring {
+    use sodiumoxide::crypto::secretbox;
+
+    pub fn load() -> Result<Option<secretbox::Key>, String> {
+        Err("No OS keyring on this platform".to_string())
+    }
+
+    pub fn store(_key: &secretbox::Key) -> Result<(), String> {
+        Err("No OS keyring on this platform".to_string())
     }
 }
 
@@ -13,6 +13,9 @@ const TOTP_STEP: u64 = 30;
 const TOTP_SKEW: u8 = 1; // ±1 window = 90s tolerance
 const TOTP_ISSUER: &str = "JANUS Monitor";
 const SECRET_BYTES: usize = 20; // 160-bit secret (RFC 4226 recommended)
+pub const RECOVERY_CODE_COUNT: usize = 10;
+// 32 symbols without 0/O/1/I: 8 chars = 40 bits, and byte % 32 stays unbiased
+const RECOVERY_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 
 /// Generate a new random TOTP secret (base32-encoded).
 pub fn generate_totp_secret() -> Result<String, String> {
@@ -55,6 +58,34 @@ pub fn verify_totp_code(secret_b32: &str, profile_name: &str, code: &str) -> Res
         .map_err(|e| format!("TOTP check error: {}", e))
 }
 
+/// Generate single-use recovery codes, formatted `XXXX-XXXX`.
+pub fn generate_recovery_codes(count: usize) -> Result<Vec<String>, String> {
+    sodiumoxide::init().map_err(|_| "sodiumoxide init failed".to_string())?;
+    Ok((0..count)
+        .map(|_| {
+            let chars: String = randombytes(8)
+                .iter()
+                .map(|b| RECOVERY_ALPHABET[(*b as usize) % RECOVERY_ALPHABET.len()] as char)
+                .collect();
+            format!("{}-{}", &chars[..4], &chars[4..])
+        })
+        .collect())
+}
+
+/// Canonical `XXXX-XXXX` form of user input (case, spaces and dash are tolerated),
+/// or None if it cannot be a recovery code.
+pub fn normalize_recovery_code(input: &str) -> Option<String> {
+    let chars: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if chars.len() != 8 || !chars.bytes().all(|b| RECOVERY_ALPHABET.contains(&b)) {
+        return None;
+    }
+    Some(format!("{}-{}", &chars[..4], &chars[4..]))
+}
+
 /// Encrypt a TOTP secret using the app-level key from SecureKeyStorage.
 /// This uses a STATIC key (not the session key) because the TOTP secret must
 /// be decrypted BEFORE the user authenticates (chicken-and-egg problem).
@@ -88,3 +119,20 @@ pub fn decrypt_totp_secret(encrypted: &str) -> Result<String, String> {
     String::from_utf8(plaintext)
         .map_err(|e| format!("Invalid UTF-8 in decrypted secret: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_codes_format_and_normalization() {
+        let codes = generate_recovery_codes(RECOVERY_CODE_COUNT).unwrap();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        for code in &codes {
+            assert_eq!(normalize_recovery_code(code).as_deref(), Some(code.as_str()));
+        }
+        assert_eq!(normalize_recovery_code(" abcd efgh ").as_deref(), Some("ABCD-EFGH"));
+        assert_eq!(normalize_recovery_code("123456"), None);
+        assert_eq!(normalize_recovery_code("ABCD-EFG0"), None);
+    }
+}
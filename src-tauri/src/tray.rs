@@ -0,0 +1,158 @@
+use crate::{DbState, MonitoringState, SessionKeyState};
+use std::sync::Arc;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, Wry};
+use tokio::sync::Mutex as TokioMutex;
+
+//
+// ICÔNE DE LA ZONE DE NOTIFICATION
+//
+
+const TRAY_ID: &str = "main";
+
+/// Menu entries whose text or state follows the app
+pub struct TrayMenu {
+    total: MenuItem<Wry>,
+    pending: MenuItem<Wry>,
+    monitoring: CheckMenuItem<Wry>,
+}
+
+/// Tray icon and menu, refreshed on `pending-tx-update` and `prices-update`
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let total = MenuItem::with_id(app, "total", "Total : —", false, None::<&str>)?;
+    let pending = MenuItem::with_id(app, "pending", "Aucune transaction en attente", true, None::<&str>)?;
+    let monitoring = CheckMenuItem::with_id(app, "monitoring", "Surveillance des transactions", true, true, None::<&str>)?;
+    let lock = MenuItem::with_id(app, "lock", "Verrouiller la session", true, None::<&str>)?;
+    let show = MenuItem::with_id(app, "show", "Afficher la fenêtre", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quitter", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let menu = Menu::with_items(app, &[&total, &pending, &separator, &monitoring, &lock, &show, &PredefinedMenuItem::separator(app)?, &quit])?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("JANUS Monitor")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "show" => show_main_window(app),
+            "pending" => {
+                show_main_window(app);
+                app.emit("navigate", "pending").ok();
+            }
+            "monitoring" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    // Registered by finish_setup: absent until an encrypted database is unlocked
+                    let Some(state) = app.try_state::<Arc<TokioMutex<MonitoringState>>>() else {
+                        log::warn!("[TRAY] Monitoring toggle ignored: database not unlocked yet");
+                        return;
+                    };
+                    let enabled = !state.lock().await.enabled;
+                    if let Err(e) = crate::apply_monitoring_enabled(&app, enabled).await {
+                        log::error!("[TRAY] Monitoring toggle failed: {}", e);
+                    }
+                });
+            }
+            "lock" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move { lock_from_tray(&app).await });
+            }
+            "quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    app.manage(TrayMenu { total, pending, monitoring });
+
+    let handle = app.clone();
+    app.listen("pending-tx-update", move |event| {
+        let txs: Vec<serde_json::Value> = serde_json::from_str(event.payload()).unwrap_or_default();
+        let count = txs.iter().filter(|tx| !tx["completed"].as_bool().unwrap_or(false)).count();
+        set_pending(&handle, count);
+    });
+    let handle = app.clone();
+    app.listen("prices-update", move |_| refresh_total(&handle));
+    Ok(())
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        window.show().ok();
+        window.unminimize().ok();
+        window.set_focus().ok();
+    }
+}
+
+/// Locks the active profile's session, like the UI's lock button (no-op when already locked)
+async fn lock_from_tray(app: &AppHandle) {
+    let unlocked = app.state::<SessionKeyState>().0.lock().map(|k| k.is_some()).unwrap_or(false);
+    if !unlocked {
+        return;
+    }
+    let profile = app.try_state::<DbState>()
+        .and_then(|db| db.0.lock().ok().and_then(|conn| crate::read_setting(&conn, "active_profile")))
+        .unwrap_or_default();
+    match crate::lock_and_notify(app, &profile).await {
+        Ok(()) => {
+            log::info!("[TRAY] Session locked from the tray");
+            refresh_total(app);
+        }
+        Err(e) => log::error!("[TRAY] Lock failed: {}", e),
+    }
+}
+
+fn set_pending(app: &AppHandle, count: usize) {
+    let text = match count {
+        0 => "Aucune transaction en attente".to_string(),
+        1 => "1 transaction en attente".to_string(),
+        n => format!("{} transactions en attente", n),
+    };
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        menu.pending.set_text(&text).ok();
+    }
+    update_tooltip(app);
+}
+
+pub fn set_monitoring(app: &AppHandle, enabled: bool) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        menu.monitoring.set_checked(enabled).ok();
+    }
+}
+
+/// Total from the last fetched prices; hidden while a protected profile is locked
+pub fn refresh_total(app: &AppHandle) {
+    let Some(menu) = app.try_state::<TrayMenu>() else { return };
+    let text = total_text(app).unwrap_or_else(|| "Total : —".to_string());
+    menu.total.set_text(&text).ok();
+    update_tooltip(app);
+}
+
+fn total_text(app: &AppHandle) -> Option<String> {
+    let prices = crate::LAST_PRICES.lock().ok()?.clone()?;
+    let db = app.try_state::<DbState>()?;
+    let conn = db.0.lock().ok()?;
+    let unlocked = app.state::<SessionKeyState>().0.lock().map(|k| k.is_some()).unwrap_or(false);
+    let profile = crate::read_setting(&conn, "active_profile").unwrap_or_default();
+    if !unlocked && !profile.is_empty() && crate::profile_has_auth(&conn, &profile) {
+        return Some("Total : 🔒".to_string());
+    }
+    let summary = crate::summarize_portfolio(&conn, &prices, chrono::Utc::now().timestamp(), crate::DEFAULT_STALE_AFTER_SECS).ok()?;
+    Some(format!("Total : {:.0} € · {:.4} BTC", summary.total_eur, summary.total_btc))
+}
+
+fn update_tooltip(app: &AppHandle) {
+    let (Some(tray), Some(menu)) = (app.tray_by_id(TRAY_ID), app.try_state::<TrayMenu>()) else { return };
+    let tooltip = format!(
+        "JANUS Monitor\n{}\n{}",
+        menu.total.text().unwrap_or_default(),
+        menu.pending.text().unwrap_or_default(),
+    );
+    tray.set_tooltip(Some(&tooltip)).ok();
+}
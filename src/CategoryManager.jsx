@@ -65,7 +65,8 @@ export default function CategoryManager({ show, onClose, categories, onUpdate, w
       return;
     }
 
-    const walletsCount = wallets.filter(w => w.category_id === category.id).length;
+    const walletsCount = await invoke('count_wallets_in_category', { categoryId: category.id })
+      .catch(() => wallets.filter(w => w.category_id === category.id).length);
     
     let message = `Supprimer la catégorie "${category.name}" ?`;
     if (walletsCount > 0) {
@@ -75,7 +76,7 @@ export default function CategoryManager({ show, onClose, categories, onUpdate, w
     if (!confirm(message)) return;
 
     try {
-      await invoke('delete_category', { id: category.id });
+      await invoke('delete_category', { id: category.id, reassignTo: null });
       await onUpdate();
       showToast(`✅ Catégorie "${category.name}" supprimée`, 2000);
     } catch (e) {
@@ -3,47 +3,9 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { secureFetchAddressHistory } from './secureBackend.js';
 
-const EXPLORERS = {
-  btc: [
-    { name: 'Mempool.space', url: (h) => `https://mempool.space/tx/${h}` },
-    { name: 'Blockstream', url: (h) => `https://blockstream.info/tx/${h}` },
-    { name: 'Blockchain.com', url: (h) => `https://www.blockchain.com/btc/tx/${h}` },
-    { name: 'Blockchair', url: (h) => `https://blockchair.com/bitcoin/transaction/${h}` },
-  ],
-  eth: [
-    { name: 'Etherscan', url: (h) => `https://etherscan.io/tx/${h}` },
-    { name: 'Ethplorer', url: (h) => `https://ethplorer.io/tx/${h}` },
-    { name: 'Blockchair', url: (h) => `https://blockchair.com/ethereum/transaction/${h}` },
-  ],
-  ltc: [
-    { name: 'Litecoin Space', url: (h) => `https://litecoinspace.org/tx/${h}` },
-    { name: 'Blockchair', url: (h) => `https://blockchair.com/litecoin/transaction/${h}` },
-    { name: 'SoChain', url: (h) => `https://chain.so/tx/LTC/${h}` },
-  ],
-  bch: [
-    { name: 'Blockchair', url: (h) => `https://blockchair.com/bitcoin-cash/transaction/${h}` },
-    { name: 'Bitcoin.com', url: (h) => `https://explorer.bitcoin.com/bch/tx/${h}` },
-    { name: 'Blockchain.com', url: (h) => `https://www.blockchain.com/bch/tx/${h}` },
-  ],
-  dot: [
-    { name: 'Subscan', url: (h) => `https://polkadot.subscan.io/extrinsic/${h}` },
-  ],
-  etc: [
-    { name: 'Blockscout', url: (h) => `https://blockscout.com/etc/mainnet/tx/${h}` },
-  ],
-};
-
-const ADDR_EXPLORERS = {
-  btc: (a) => `https://mempool.space/address/${a}`,
-  eth: (a) => `https://etherscan.io/address/${a}`,
-  ltc: (a) => `https://litecoinspace.org/address/${a}`,
-  bch: (a) => `https://blockchair.com/bitcoin-cash/address/${a}`,
-  dot: (a) => `https://polkadot.subscan.io/account/${a}`,
-  etc: (a) => `https://blockscout.com/etc/mainnet/address/${a}`,
-};
-
-const openUrl = (url) => {
-  invoke('open_url', { url }).catch(() => {});
+// Explorer URLs come from the asset registry (get_assets templates), opened by the backend
+const openExplorer = (asset, kind, value) => {
+  invoke('open_explorer', { asset, kind, value }).catch(() => {});
 };
 
 export default function PendingTransactionsPanel({ show, onClose, onBackToMenu, wallets = [], theme = 'dark' }) {
@@ -57,6 +19,7 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
   const [showCsvExport, setShowCsvExport] = useState(false);
   const [csvMsg, setCsvMsg] = useState('');
   const [csvSelectedWallets, setCsvSelectedWallets] = useState(new Set()); // empty = all
+  const [addressExplorers, setAddressExplorers] = useState(new Set()); // assets with an address URL template
 
   const isDark = theme === 'dark' || theme === 'noctali' || theme === 'lunarpunk';
   const isLP = theme === 'lunarpunk';
@@ -86,6 +49,9 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
 
   useEffect(() => {
     if (!show) return;
+    invoke('get_assets')
+      .then(assets => setAddressExplorers(new Set(assets.filter(a => a.explorer_address_url_template).map(a => a.symbol))))
+      .catch(() => {});
     loadPendingTransactions();
     const setup = async () => {
       const unlisten = await listen('pending-tx-update', (event) => {
@@ -111,9 +77,11 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
     setHistoryTxs([]);
     try {
       let ethKey = '';
-      try { ethKey = await invoke('get_setting', { key: 'etherscan_api_key' }); } catch(_) {}
+      try { ethKey = (await invoke('get_setting', { key: 'etherscan_api_key' })) ?? ''; } catch(_) {}
+      // Sealed key while locked: the placeholder is not a key
+      if (ethKey === '••••••••') ethKey = '';
 
-      const supportedAssets = ['btc', 'eth', 'ltc', 'bch', 'dot', 'etc'];
+      const supportedAssets = ['btc', 'eth', 'ltc', 'bch', 'dot', 'etc', 'doge', 'dash', 'xrp', 'sol', 'ada'];
       const walletsWithAddr = wallets.filter(w => w.address && w.address.trim() && supportedAssets.includes(w.asset?.toLowerCase()));
 
       const promises = walletsWithAddr.map(w =>
@@ -125,7 +93,7 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
           10
         ).catch(() => {
           showToast(`⚠️ Erreur historique pour ${w.asset.toUpperCase()}`, 2000);
-          return [];
+          return { txs: [], has_more: false, cursor: null };
         })
       );
 
@@ -133,10 +101,10 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
       
       // Validation des résultats
       const validatedResults = results.map(result => {
-        if (!Array.isArray(result)) {
+        if (!result || !Array.isArray(result.txs)) {
           return [];
         }
-        return result.filter(tx => 
+        return result.txs.filter(tx => 
           tx && tx.tx_hash && tx.asset && typeof tx.timestamp === 'number'
         );
       });
@@ -170,10 +138,10 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
     const filtered = getFilteredCsvTxs();
     if (filtered.length === 0) { setCsvMsg('Aucune TX dans cette période'); setTimeout(() => setCsvMsg(''), 2000); return; }
 
-    const header = 'Date,Heure,Asset,Direction,Montant,Wallet,Adresse,De (From),Vers (To),Hash TX,Block,Confirmations\n';
+    const header = 'Date,Heure,Asset,Direction,Montant,Frais,Valeur EUR (date TX),Valeur USD (date TX),Wallet,Adresse,De (From),Libellé De,Vers (To),Libellé Vers,Hash TX,Block,Confirmations\n';
     const rows = filtered.map(tx => {
       const d = new Date(tx.timestamp * 1000);
-      return `${d.toLocaleDateString('fr-FR')},${d.toLocaleTimeString('fr-FR')},${tx.asset.toUpperCase()},${tx.direction === 'in' ? 'Reçu' : 'Envoyé'},${tx.amount},"${tx.wallet_name}","${tx.address}","${tx.from_address || ''}","${tx.to_address || ''}","${tx.tx_hash}",${tx.block_height},${tx.confirmations}`;
+      return `${d.toLocaleDateString('fr-FR')},${d.toLocaleTimeString('fr-FR')},${tx.asset.toUpperCase()},${tx.direction === 'in' ? 'Reçu' : 'Envoyé'},${tx.amount},${tx.fee || 0},${tx.value_eur_at_time ?? ''},${tx.value_usd_at_time ?? ''},"${tx.wallet_name}","${tx.address}","${tx.from_address || ''}","${tx.from_label || ''}","${tx.to_address || ''}","${tx.to_label || ''}","${tx.tx_hash}",${tx.block_height},${tx.confirmations}`;
     }).join('\n');
 
     const csv = '\uFEFF' + header + rows; // BOM for Excel FR
@@ -182,10 +150,11 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
     const filename = `janus_export_${fromLabel}_${toLabel}.csv`;
 
     try {
-      const home = await invoke('get_home_dir').catch(() => '/home/user');
-      const basePath = `${home}/Téléchargements/${filename}`;
+      const dir = await invoke('get_default_export_dir');
+      const sep = dir.includes('\\') ? '\\' : '/';
+      const basePath = `${dir}${sep}${filename}`;
       await invoke('save_csv_file', { path: basePath, content: csv });
-      setCsvMsg(`✓ ${filtered.length} TX → ~/Téléchargements/${filename}`);
+      setCsvMsg(`✓ ${filtered.length} TX → ${basePath}`);
       setTimeout(() => setCsvMsg(''), 4000);
     } catch(e) {
       // Fallback: clipboard
@@ -302,10 +271,10 @@ export default function PendingTransactionsPanel({ show, onClose, onBackToMenu,
                     <div className={`text-center py-3 space-y-1`}>
                       <div className={`text-xs ${T.textFaint}`}>10 dernières TX par wallet</div>
                       {wallets.filter(w => w.address).map(w => {
-                        const fn = ADDR_EXPLORERS[w.asset?.toLowerCase()];
-                        if (!fn) return null;
+                        const asset = w.asset?.toLowerCase();
+                        if (!addressExplorers.has(asset)) return null;
                         return (
-                          <button key={w.id} onClick={() => openUrl(fn(w.address))}
+                          <button key={w.id} onClick={() => openExplorer(asset, 'address', w.address)}
                             className={`block w-full text-xs hover:underline`} style={{ color: accentColor }}>
                             {w.name || w.asset.toUpperCase()} — voir tout l'historique →
                           </button>
@@ -490,7 +459,7 @@ function PendingTxCard({ tx, T, onClear, fmt, fmtTime, walletName, walletAddress
             </div>
           )}
           <div className="flex justify-between items-center pt-1">
-            <ExplorerDropdown asset={tx.asset} txHash={tx.tx_hash} T={T} />
+            <ExplorerLink asset={tx.asset} txHash={tx.tx_hash} T={T} />
             {done && <button onClick={(e) => { e.stopPropagation(); onClear(tx.tx_hash); }} className={`text-[10px] ${T.textFaint} hover:text-red-400`}>✕ Retirer</button>}
           </div>
         </div>
@@ -503,7 +472,38 @@ function PendingTxCard({ tx, T, onClear, fmt, fmtTime, walletName, walletAddress
 function HistoryTxCard({ tx, T, fmt, fmtTime, accentColor }) {
   const [expanded, setExpanded] = useState(false);
   const [copied, setCopied] = useState('');
+  // Address book: labels saved from this card, by side ('from' / 'to')
+  const [bookLabels, setBookLabels] = useState({});
+  const [bookEdit, setBookEdit] = useState(null);
+  const [bookInput, setBookInput] = useState('');
   const isIn = tx.direction === 'in';
+  const fromLabel = bookLabels.from || tx.from_label;
+  const toLabel = bookLabels.to || tx.to_label;
+  const counterparty = isIn ? fromLabel : toLabel;
+
+  const saveBookEntry = async (side, address) => {
+    const label = bookInput.trim();
+    if (!label) return;
+    try {
+      await invoke('add_address_book_entry', { address, asset: tx.asset, label, note: null });
+      setBookLabels(prev => ({ ...prev, [side]: label }));
+      setBookEdit(null); setBookInput('');
+    } catch (e) { setCopied(`${e}`); setTimeout(() => setCopied(''), 3000); }
+  };
+
+  const bookControl = (side, address, label) => label
+    ? <span className="ml-1 text-[10px] text-amber-500">{label}</span>
+    : bookEdit === side
+      ? (
+        <span className="flex items-center gap-1 mt-1" onClick={(e) => e.stopPropagation()}>
+          <input autoFocus value={bookInput} onChange={(e) => setBookInput(e.target.value)} placeholder="Libellé"
+            onKeyDown={(e) => { if (e.key === 'Enter') saveBookEntry(side, address); if (e.key === 'Escape') setBookEdit(null); }}
+            className={`flex-1 px-2 py-0.5 text-[11px] ${T.inputBg} border ${T.inputBorder} rounded`} />
+          <button onClick={() => saveBookEntry(side, address)} className="text-[10px] text-amber-500">OK</button>
+        </span>
+      )
+      : <button onClick={(e) => { e.stopPropagation(); setBookEdit(side); setBookInput(''); }}
+          className={`ml-1 text-[9px] ${T.textFaint} hover:text-amber-500`}>+ carnet</button>;
 
   const doCopy = (text, label) => {
     navigator.clipboard.writeText(text).then(() => {
@@ -518,7 +518,9 @@ function HistoryTxCard({ tx, T, fmt, fmtTime, accentColor }) {
       <div className="p-3 cursor-pointer" onClick={() => setExpanded(!expanded)}>
         <div className="flex items-center justify-between mb-1">
           <span className={`text-xs font-medium ${T.text}`}>{tx.wallet_name}</span>
-          <span className={`text-[10px] ${isIn ? 'text-green-500' : 'text-red-400'}`}>{isIn ? '↓ Reçu' : '↑ Envoyé'}</span>
+          <span className={`text-[10px] ${isIn ? 'text-green-500' : 'text-red-400'}`}>
+            {isIn ? '↓ Reçu' : '↑ Envoyé'}{counterparty && ` ${isIn ? 'de' : 'vers'} ${counterparty}`}
+          </span>
         </div>
         <div className="flex items-center justify-between">
           <div className="flex items-baseline gap-2">
@@ -547,7 +549,7 @@ function HistoryTxCard({ tx, T, fmt, fmtTime, accentColor }) {
           {/* From address */}
           {tx.from_address && (
             <div>
-              <div className={`text-[10px] ${T.textFaint} mb-0.5`}>De (From)</div>
+              <div className={`text-[10px] ${T.textFaint} mb-0.5`}>De (From){bookControl('from', tx.from_address, fromLabel)}</div>
               <button onClick={(e) => { e.stopPropagation(); doCopy(tx.from_address, 'from'); }}
                 className={`w-full text-left text-[11px] font-mono ${isIn ? T.textMuted : ''} hover:text-amber-500 transition-colors break-all leading-relaxed`}
                 style={!isIn ? { color: accentColor } : {}}>
@@ -559,7 +561,7 @@ function HistoryTxCard({ tx, T, fmt, fmtTime, accentColor }) {
           {/* To address */}
           {tx.to_address && (
             <div>
-              <div className={`text-[10px] ${T.textFaint} mb-0.5`}>Vers (To)</div>
+              <div className={`text-[10px] ${T.textFaint} mb-0.5`}>Vers (To){bookControl('to', tx.to_address, toLabel)}</div>
               <button onClick={(e) => { e.stopPropagation(); doCopy(tx.to_address, 'to'); }}
                 className={`w-full text-left text-[11px] font-mono ${isIn ? '' : T.textMuted} hover:text-amber-500 transition-colors break-all leading-relaxed`}
                 style={isIn ? { color: accentColor } : {}}>
@@ -569,41 +571,26 @@ function HistoryTxCard({ tx, T, fmt, fmtTime, accentColor }) {
             </div>
           )}
           {tx.block_height > 0 && (
-            <div className={`text-[10px] ${T.textFaint}`}>Block #{tx.block_height.toLocaleString()}</div>
+            <div className={`text-[10px] ${T.textFaint}`}>
+              Block #{tx.block_height.toLocaleString()}
+              {tx.required_confirmations > 0 && (tx.confirmations < tx.required_confirmations
+                ? <span className="ml-1 text-blue-500">· {tx.confirmations}/{tx.required_confirmations} confirmations</span>
+                : <span className="ml-1 text-green-500">· finalisée</span>)}
+            </div>
           )}
-          <ExplorerDropdown asset={tx.asset} txHash={tx.tx_hash} T={T} />
+          <ExplorerLink asset={tx.asset} txHash={tx.tx_hash} T={T} />
         </div>
       )}
     </div>
   );
 }
 
-/* ── Explorer dropdown ── */
-function ExplorerDropdown({ asset, txHash, T }) {
-  const [open, setOpen] = useState(false);
-  const explorers = EXPLORERS[asset.toLowerCase()] || EXPLORERS.btc;
-
+/* ── Explorer link ── */
+function ExplorerLink({ asset, txHash, T }) {
   return (
-    <div className="relative">
-      <button onClick={(e) => { e.stopPropagation(); setOpen(!open); }}
-        className={`text-[10px] ${T.textFaint} hover:text-amber-500 transition-colors flex items-center gap-0.5`}>
-        Explorer
-        <svg xmlns="http://www.w3.org/2000/svg" width="8" height="8" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2.5" strokeLinecap="round" strokeLinejoin="round"><polyline points="6 9 12 15 18 9"/></svg>
-      </button>
-      {open && (
-        <>
-          <div className="fixed inset-0 z-10" onClick={() => setOpen(false)} />
-          <div className={`absolute bottom-5 left-0 z-20 ${T.cardBg} border ${T.border} rounded-lg shadow-xl py-1 min-w-[160px]`}>
-            {explorers.map(ex => (
-              <button key={ex.name}
-                onClick={(e) => { e.stopPropagation(); openUrl(ex.url(txHash)); setOpen(false); }}
-                className={`block w-full text-left px-3 py-1.5 text-xs ${T.textMuted} hover:text-amber-500 transition-colors`}>
-                {ex.name} →
-              </button>
-            ))}
-          </div>
-        </>
-      )}
-    </div>
+    <button onClick={(e) => { e.stopPropagation(); openExplorer(asset.toLowerCase(), 'tx', txHash); }}
+      className={`text-[10px] ${T.textFaint} hover:text-amber-500 transition-colors`}>
+      Explorer →
+    </button>
   );
 }
@@ -32,7 +32,8 @@ export const PRIVACY_COINS = {
     symbol: 'PIVX',
     decimals: 8,
     type: 'zerocoin',
-    defaultNode: 'http://pivx.api.btc.com',
+    // No node: the backend reads balances from the blockbook explorer (explorer.pivx.link)
+    defaultNode: null,
     explorer: 'https://chainz.cryptoid.info/pivx/',
     rpcPort: 51473,
     zerocoinMinConfirmations: 6,
@@ -146,7 +147,8 @@ export const getPrivacyBalance = async (symbol, address, options = {}) => {
       return invoke('get_monero_balance', {
         address,
         viewKey: options.viewKey,
-        node: options.node || coinInfo.defaultNode
+        node: options.node || coinInfo.defaultNode,
+        subaddressIndex: options.subaddressIndex || null
       });
     
     case 'PIVX':
@@ -189,6 +191,9 @@ export const getPrivacyTransactions = async (symbol, address, options = {}) => {
       return invoke('get_monero_transactions', {
         address,
         viewKey: options.viewKey,
+        spendKey: options.spendKey || null,
+        node: options.node || null,
+        subaddressIndex: options.subaddressIndex || null,
         limit
       });
     
@@ -223,7 +228,12 @@ export const getPrivacyTransactions = async (symbol, address, options = {}) => {
 export const testPrivacyNode = async (symbol, node, credentials = {}) => {
   switch (symbol) {
     case 'XMR':
-      return invoke('test_monero_node', { nodeUrl: node });
+      return invoke('test_monero_node', {
+        nodeUrl: node,
+        rpcUser: credentials.rpcUser || null,
+        rpcPassword: credentials.rpcPassword || null,
+        allowSelfSigned: !!credentials.allowSelfSigned
+      });
     
     case 'PIVX':
       return invoke('test_pivx_node', {
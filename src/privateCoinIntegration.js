@@ -70,10 +70,10 @@ const getPivxBalance = async (walletData) => {
     
     return {
       success: true,
-      zerocoinBalance: result.zerocoinBalance,
-      regularBalance: result.regularBalance,
-      totalBalance: result.totalBalance,
-      transactions: result.transactions || []
+      transparentBalance: result.transparent_balance,
+      coldStakingBalance: result.cold_staking_balance, // null without RPC credentials
+      shieldBalance: result.shield_balance, // always null: manual entry
+      totalBalance: result.total_balance
     };
   } catch (error) {
     console.error('Erreur PIVX:', error);
@@ -202,8 +202,8 @@ const getMoneroTransactionHistory = async (walletData, limit = 20) => {
 
     return {
       success: true,
-      transactions: result.transactions || [],
-      totalTransactions: result.totalTransactions || 0
+      transactions: Array.isArray(result) ? result : [],
+      totalTransactions: Array.isArray(result) ? result.length : 0
     };
   } catch (error) {
     console.error('Erreur historique Monero:', error);
@@ -53,21 +53,34 @@ const secureGetPrices = async () => {
   ]);
 };
 
-const secureFetchBalance = async (asset, address) => {
-  return secureInvoke('fetch_balance', { asset, address }, 'number');
+const secureFetchBalance = async (asset, address, forceRefresh = false, walletId = null) => {
+  return secureInvoke('fetch_balance', { asset, address, forceRefresh, walletId }, 'number');
 };
 
-const secureFetchAddressHistory = async (address, asset, walletName, etherscanKey, limit) => {
+const secureFetchAddressHistory = async (address, asset, walletName, etherscanKey, limit, refresh = false, cursor = null) => {
   return secureInvoke('fetch_address_history', {
     address,
     asset,
     walletName,
     etherscanKey,
-    limit
-  }, 'array');
+    limit,
+    refresh,
+    cursor
+  }, 'object', ['txs', 'has_more']);
+};
+
+// Erreurs structurées (JanusError) : { code, message, user_message, retry_after, provider }.
+// Les commandes pas encore migrées rejettent encore une simple chaîne.
+const errorCode = (error) => (error && typeof error === 'object' && error.code) || null;
+
+const errorMessage = (error) => {
+  if (error && typeof error === 'object' && error.code) return error.user_message || error.message;
+  return String(error);
 };
 
 export {
+  errorCode,
+  errorMessage,
   secureInvoke,
   secureGetPrices,
   secureFetchBalance,